@@ -0,0 +1,21 @@
+use std::path::Path;
+
+/// Window icon embedded into the binary when present.
+const ICON_PATH: &str = "assets/icon.png";
+
+/// Optional CJK font embedded as a last-resort fallback; not checked in by default.
+const FONT_PATH: &str = "assets/fonts/cjk-fallback.ttf";
+
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(has_app_icon)");
+    println!("cargo::rustc-check-cfg=cfg(has_bundled_font)");
+    // Watch the folder: a missing file would make cargo rerun this on every build
+    println!("cargo::rerun-if-changed=assets");
+    // Builds from a checkout without the assets still work, just without them
+    if Path::new(ICON_PATH).is_file() {
+        println!("cargo::rustc-cfg=has_app_icon");
+    }
+    if Path::new(FONT_PATH).is_file() {
+        println!("cargo::rustc-cfg=has_bundled_font");
+    }
+}
@@ -1,12 +1,65 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 use eframe::egui::{self, Color32, RichText, ScrollArea};
 use serde::{Deserialize, Serialize};
 
-use crate::pipeline::{Pipeline, StepStatus, STEPS};
-use crate::runner::{self, LogLine, RunHandle};
-use crate::settings::{FieldType, Settings, SETTING_GROUPS};
-use crate::widgets::timeline;
+use crate::audio::{self, AudioFormat, SegmentEngines};
+use crate::diff::{self, DiffLine};
+use crate::fonts;
+use crate::pdf::{self, PdfProbe};
+use crate::pipeline::{self, GenerationParams, Pipeline, StepStatus, STEPS};
+use crate::runner::{self, LogLevel, LogLine, PipelineEvent, Redactor, RunHandle};
+use crate::script::{Script, KNOWN_SPEAKERS};
+use crate::series::{self, SeriesList};
+use crate::settings::{self, FieldType, SettingGroups, Settings, TtsEngine, SETTING_GROUPS, TTS_VOICES, TTS_VOICE_KEYS};
+use crate::undo::TextHistory;
+use crate::update::{self, UpdateCheck};
+use crate::widgets::toast::Toasts;
+use crate::widgets::{script_editor, timeline};
+
+/// Summary of the most recently published episode.
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct LastEpisode {
+    title: String,
+    date: String,
+    work_dir: PathBuf,
+    #[serde(default)]
+    mp3_path: String,
+    #[serde(default)]
+    cdn_url: String,
+}
+
+impl LastEpisode {
+    fn from_work_dir(work_dir: &Path) -> Option<Self> {
+        let meta = read_metadata(work_dir)?;
+        let field = |key: &str| meta.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        Some(Self {
+            title: field("title"),
+            date: field("date"),
+            work_dir: work_dir.to_path_buf(),
+            mp3_path: metadata_audio_path(&meta).unwrap_or_default().to_string(),
+            cdn_url: metadata_audio_url(&meta).unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// The step being run, persisted while it runs; still present on the next
+/// start means the app did not exit normally.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct RunningStep {
+    step: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pdf_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output_dir: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    work_dir: Option<PathBuf>,
+}
 
 /// Persisted recent directory paths (saved independently).
 #[derive(Default, Serialize, Deserialize)]
@@ -15,8 +68,52 @@ struct RecentPaths {
     last_pdf_dir: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     last_output_dir: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_episode: Option<LastEpisode>,
+    /// Active `.env.<profile>`; absent means the plain `.env`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_profile: Option<String>,
+    /// Last-used speaker → voice assignment.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    voice_mapping: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_font_size: Option<f32>,
+    #[serde(default)]
+    log_wrap: bool,
+    /// Lowest log level shown; absent shows everything.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_min_level: Option<LogLevel>,
+    #[serde(default)]
+    compact_mode: bool,
+    /// Debugging aid: don't mask secret values in the log.
+    #[serde(default)]
+    log_show_secrets: bool,
+    #[serde(default)]
+    audio_format: AudioFormat,
+    /// Chinese font that loaded last time, tried before the other candidates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ui_scale: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ui_font_size: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    running_step: Option<RunningStep>,
+    /// Series the last episodes were published into.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    series_id: Option<String>,
 }
 
+/// How long quitting waits for a killed job to stop before closing anyway.
+const EXIT_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+/// Autosave interval for unsaved script edits when `SCRIPT_AUTOSAVE_SECONDS` is unset.
+const DEFAULT_AUTOSAVE_SECS: u64 = 30;
+/// Sidecar next to script.json holding unsaved edits; script.json itself is untouched.
+const SCRIPT_AUTOSAVE_FILE: &str = "script.json.autosave";
+
+/// Minimum time between writes of `podcast-studio.json`.
+const RECENT_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
 impl RecentPaths {
     fn config_path(project_root: &Path) -> PathBuf {
         project_root.join("podcast-studio.json")
@@ -33,7 +130,151 @@ impl RecentPaths {
     fn save(&self, project_root: &Path) {
         let path = Self::config_path(project_root);
         if let Ok(json) = serde_json::to_string_pretty(self) {
-            let _ = std::fs::write(path, json);
+            let _ = settings::write_atomic(&path, json.as_bytes());
+        }
+    }
+}
+
+/// What `publish-podcast --dry-run` would upload, from `publish_preview.json`.
+#[derive(Deserialize)]
+struct PublishPreview {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    digest: String,
+    #[serde(default)]
+    source_url: String,
+    #[serde(default)]
+    cover_path: String,
+    #[serde(default)]
+    mp3_url: String,
+    #[serde(default)]
+    html_path: String,
+    #[serde(default)]
+    html_chars: usize,
+}
+
+impl PublishPreview {
+    const FILE_NAME: &'static str = "publish_preview.json";
+
+    fn load(work_dir: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(work_dir.join(Self::FILE_NAME))
+            .map_err(|_| format!("未生成 {}，Python 管线可能不支持预览", Self::FILE_NAME))?;
+        serde_json::from_str(&content).map_err(|e| format!("{} 格式错误: {e}", Self::FILE_NAME))
+    }
+}
+
+/// WeChat rejects draft titles longer than this many characters.
+const WECHAT_TITLE_MAX: usize = 64;
+/// Longer digests are cut off by the pipeline.
+const WECHAT_DIGEST_MAX: usize = 120;
+
+/// Draft fields editable before publishing, stored in `metadata.json`.
+struct DraftFields {
+    work_dir: PathBuf,
+    title: String,
+    digest: String,
+    author: String,
+}
+
+impl DraftFields {
+    /// Read the fields, filling gaps the way `publish-podcast` would.
+    fn load(work_dir: &Path) -> Self {
+        let meta = read_metadata(work_dir).unwrap_or_default();
+        let field = |key: &str| meta.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let mut title = field("title");
+        if title.is_empty() {
+            title = std::fs::read_to_string(work_dir.join("script.json"))
+                .ok()
+                .and_then(|content| Script::parse(&content).ok())
+                .map(|script| script.title)
+                .unwrap_or_default();
+        }
+        Self {
+            work_dir: work_dir.to_path_buf(),
+            digest: field("digest"),
+            author: field("author"),
+            title,
+        }
+    }
+
+    /// Merge the fields into `metadata.json`, keeping every other key.
+    fn save(&self) -> Result<(), String> {
+        let mut meta = read_metadata(&self.work_dir).unwrap_or_else(|| serde_json::json!({}));
+        let Some(object) = meta.as_object_mut() else {
+            return Err("metadata.json 格式错误".to_string());
+        };
+        object.insert("title".to_string(), self.title.trim().into());
+        // Empty means "use the default", which the pipeline decides
+        for (key, value) in [("digest", &self.digest), ("author", &self.author)] {
+            if value.trim().is_empty() {
+                object.remove(key);
+            } else {
+                object.insert(key.to_string(), value.trim().into());
+            }
+        }
+        let json = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
+        settings::write_atomic(&self.work_dir.join("metadata.json"), json.as_bytes())
+            .map_err(|e| format!("无法写入 metadata.json: {e}"))
+    }
+}
+
+/// A named section of the log started by a `>>> PHASE:` marker.
+struct LogPhase {
+    name: String,
+    /// Index into `log_lines` of the marker line.
+    start: usize,
+}
+
+/// One spawned pipeline command, kept so it can be run again.
+struct CommandRecord {
+    step: usize,
+    args: Vec<String>,
+    /// Per-run overrides only; profile values are re-applied by `spawn`.
+    envs: Vec<(String, String)>,
+    started_at: String,
+    status: StepStatus,
+}
+
+/// Pending choice after script.json changed on disk while the user had unsaved edits.
+struct ScriptConflict {
+    disk_content: String,
+    show_diff: bool,
+}
+
+/// Autosaved edits found newer than script.json when loading it.
+struct ScriptAutosave {
+    content: String,
+    show_diff: bool,
+}
+
+/// What the views read from the current work dir, loaded once rather than every frame.
+struct EpisodeFiles {
+    work_dir: PathBuf,
+    metadata: Option<serde_json::Value>,
+    generation: Option<GenerationParams>,
+    engines: Option<SegmentEngines>,
+    /// The audio named in metadata.json, or the first audio file in the work dir.
+    audio: Option<audio::AudioInfo>,
+}
+
+impl EpisodeFiles {
+    fn read(work_dir: &Path) -> Self {
+        let metadata = read_metadata(work_dir);
+        let audio_path = metadata
+            .as_ref()
+            .and_then(metadata_audio_path)
+            .map(PathBuf::from)
+            .or_else(|| find_audio(work_dir));
+        let audio = audio_path.as_deref().and_then(audio::probe_audio);
+        Self {
+            work_dir: work_dir.to_path_buf(),
+            metadata,
+            generation: GenerationParams::load(work_dir),
+            engines: SegmentEngines::load(work_dir),
+            audio,
         }
     }
 }
@@ -50,124 +291,571 @@ pub struct PodcastApp {
     page: Page,
     pipeline: Pipeline,
     log_lines: Vec<LogLine>,
+    /// Phase sections detected in `log_lines`.
+    log_phases: Vec<LogPhase>,
+    /// Number of phases already rendered, to collapse older ones when a new one starts.
+    log_phases_seen: usize,
+    /// Python tracebacks found in `log_lines`.
+    log_tracebacks: Vec<Range<usize>>,
+    /// Scroll the log to the first traceback on the next frame.
+    scroll_to_traceback: bool,
+    /// Lines of `log_lines` already written to the step's log file.
+    log_saved: usize,
+    /// Step that produced the current `log_lines`.
+    log_step: usize,
+    /// Collapse consecutive identical log lines into one with a counter.
+    collapse_log_duplicates: bool,
+    /// Keep the log scrolled to the newest line while it grows.
+    log_follow: bool,
+    /// Fold the log to its last line unless expanded ("紧凑模式").
+    compact_mode: bool,
+    log_show_secrets: bool,
+    /// Masks the settings' secrets in the running step's output; built at spawn.
+    log_redactor: Redactor,
+    /// The compact log was already opened for this run's failure; the user may fold it again.
+    log_opened_on_failure: bool,
+    /// Jump to the bottom on the next frame, after following is turned back on.
+    log_scroll_to_end: bool,
+    log_wrap: bool,
+    log_font_size: f32,
+    /// Hide leveled log lines below this; lines without a level always show.
+    log_min_level: Option<LogLevel>,
+    /// Zoom on top of the display's own scaling, as shown in the settings slider.
+    ui_scale: f32,
+    /// Zoom last applied to the context; differs from `ui_scale` mid-drag.
+    ui_scale_applied: f32,
+    /// Body text size; other text styles scale with it.
+    ui_font_size: f32,
     run_handle: Option<RunHandle>,
+    /// Commands spawned for the current podcast, oldest first.
+    command_history: Vec<CommandRecord>,
+    /// TTS engine picked for the next audio run.
+    tts_engine: TtsEngine,
+    /// Container for the next audio run.
+    audio_format: AudioFormat,
+    /// Speaker → voice assignment written to `voices.json` for the audio step.
+    voice_mapping: BTreeMap<String, String>,
+    /// Speakers found in script.json, keyed by the work dir they were read from.
+    script_speakers: Option<(PathBuf, Vec<String>)>,
+    /// Show the structured editor instead of raw JSON.
+    script_structured: bool,
+    /// Turn to scroll the structured editor to, set by a click in the outline.
+    script_scroll_to: Option<usize>,
+    /// `script_content` parsed for the structured editor, with the text it came from.
+    script_model: Option<(String, Result<Script, String>)>,
+    /// Turns ticked in the structured editor for synthesizing again, with the
+    /// work dir they belong to.
+    selected_segments: (Option<PathBuf>, BTreeSet<usize>),
+    /// Synthesize only `selected_segments` in the next audio run.
+    audio_selected_only: bool,
+    /// Settings used by the running step, recorded into work_dir on success.
+    run_params: GenerationParams,
     script_content: String,
+    /// Content of script.json as last loaded from or saved to disk.
+    script_baseline: String,
     script_dirty: bool,
+    /// Showing the unsaved edits as a diff against `script_baseline`.
+    script_show_changes: bool,
+    /// Diff shown by "查看更改", with the baseline and content it was computed from.
+    script_changes: Option<(String, String, Vec<DiffLine<String>>)>,
+    /// Snapshots of `script_content` for the JSON editor's Ctrl+Z / Ctrl+Shift+Z.
+    script_history: TextHistory,
+    script_conflict: Option<ScriptConflict>,
+    /// Sidecar that unsaved edits of the loaded script.json are autosaved to.
+    script_autosave_path: Option<PathBuf>,
+    /// Last autosave, or when the edits became unsaved.
+    script_autosaved_at: Instant,
+    script_autosave_offer: Option<ScriptAutosave>,
+    /// Cached files of the work dir; cleared whenever a step may have rewritten them.
+    episode_files: Option<EpisodeFiles>,
+    /// Error from the last attempt to launch the custom editor.
+    editor_error: String,
+    /// Transient feedback such as "已复制" and run results.
+    toasts: Toasts,
+    /// Chaining steps automatically ("一键生成全部").
+    run_all: bool,
+    /// During a run-all, go straight from script to audio without editing.
+    run_all_skip_edit: bool,
+    /// Per-step "完成后停止": don't auto-advance when that step succeeds.
+    stop_after: Vec<bool>,
+    /// The running publish job is a `--dry-run` preview.
+    publish_dry_run: bool,
+    /// The next publish uploads the audio and cover again (`--upload-audio`)
+    /// instead of reusing what an earlier, possibly cancelled, run uploaded.
+    publish_upload_audio: bool,
+    publish_preview: Option<Result<PublishPreview, String>>,
+    /// Editable WeChat title/digest/author of the work dir being published.
+    draft_fields: Option<DraftFields>,
+    /// Episode folders found by scanning the output dirs, newest first.
+    work_dir_candidates: Option<Vec<PathBuf>>,
+    /// Work dir that disappeared from disk, shown until another one is picked.
+    work_dir_missing: Option<PathBuf>,
+    /// Result of the write test run when the output folder was picked.
+    output_dir_writable: bool,
     settings: Settings,
+    /// Field layout of the settings page, from the project schema or built-in.
+    setting_groups: SettingGroups,
     settings_status: String,
+    /// Setting highlighted after jumping from an error hint.
+    settings_focus: Option<&'static str>,
+    scroll_to_focus: bool,
+    confirm_settings_reload: bool,
+    /// Name typed into the "new profile" field.
+    new_profile_name: String,
+    /// The .env file was modified outside the app since it was loaded.
+    env_changed_externally: bool,
+    /// When the .env mtime was last polled.
+    env_checked_at: Instant,
+    /// `RecentPaths` state changed since it was last written.
+    recent_dirty: bool,
+    recent_saved_at: Instant,
+    /// Background first-page text extraction for the selected PDF.
+    pdf_probe: Option<PdfProbe>,
+    /// Why the picked file can't be used as a PDF, checked when it is picked.
+    pdf_error: Option<String>,
     /// Last directory used for PDF file picker.
     last_pdf_dir: Option<PathBuf>,
     /// Last directory used for output folder picker.
     last_output_dir: Option<PathBuf>,
+    /// Last successfully published episode, shown on the start page.
+    last_episode: Option<LastEpisode>,
     /// Project root for saving recent paths.
     project_root: PathBuf,
+    /// Series from `series.json`, edited on the PDF step.
+    series: SeriesList,
+    /// Series the current episode is published into, if any.
+    series_id: Option<String>,
+    /// Episode number within `series_id`, the series' next one unless changed.
+    series_episode: u32,
+    new_series_name: String,
+    series_error: String,
+    /// Step recorded as running in `podcast-studio.json`.
+    running_step: Option<RunningStep>,
+    /// Left running by the previous session; offered for resuming until answered.
+    interrupted_run: Option<RunningStep>,
+    /// Showing the "task running, really quit?" prompt.
+    confirm_exit: bool,
+    /// Publishing creates a real draft, so it waits for this dialog.
+    confirm_publish: bool,
+    /// Going back to regenerate would overwrite an existing script.json.
+    confirm_regenerate: bool,
+    regenerate_backup: bool,
+    /// Keep pointing at the current work dir; otherwise forget it until the new run reports one.
+    regenerate_keep_work_dir: bool,
+    regenerate_error: String,
+    /// User confirmed quitting; let the next close request through.
+    exit_confirmed: bool,
+    /// Quitting with a job running: when the job was killed, closing once it has stopped.
+    exit_stopping_since: Option<Instant>,
+    /// Font file the background loader picked, once it reports back.
+    font_rx: Option<mpsc::Receiver<PathBuf>>,
+    font_path: Option<PathBuf>,
+    /// Started from the "检查更新" button; nothing is fetched otherwise.
+    update_check: Option<UpdateCheck>,
 }
 
 impl PodcastApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        // Load system Chinese font for CJK character support
-        Self::setup_fonts(&cc.egui_ctx);
-
         // Find project root (parent of podcast-studio/)
-        let project_root = find_project_root();
-        let settings = Settings::load(&project_root);
+        let project_root = runner::find_project_root(&runner::Entrypoint::from_env());
         let recent = RecentPaths::load(&project_root);
+        let series = SeriesList::load(&project_root);
+        // A profile whose file was deleted falls back to the default .env
+        let profile = recent
+            .active_profile
+            .clone()
+            .filter(|p| settings::available_profiles(&project_root).contains(p));
+        let settings = Settings::load(&project_root, profile.as_deref());
+
+        // Load a Chinese font for CJK character support: the configured one,
+        // then the one that worked last time, then the platform's usual fonts
+        let configured = settings.get("CJK_FONT_PATH").trim();
+        let preferred: Vec<PathBuf> = (!configured.is_empty())
+            .then(|| PathBuf::from(configured))
+            .into_iter()
+            .chain(recent.font_path.clone())
+            .collect();
+        let font_rx = fonts::load_in_background(&cc.egui_ctx, preferred);
+
+        let ui_scale = recent.ui_scale.unwrap_or(1.0).clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+        let ui_font_size = recent
+            .ui_font_size
+            .unwrap_or(DEFAULT_UI_FONT_SIZE)
+            .clamp(MIN_UI_FONT_SIZE, MAX_UI_FONT_SIZE);
+        cc.egui_ctx.set_zoom_factor(ui_scale);
+        apply_font_size(&cc.egui_ctx, ui_font_size);
+        let (setting_groups, settings_status) = match settings::load_setting_groups(&project_root) {
+            Ok(groups) => (groups, String::new()),
+            Err(e) => (SETTING_GROUPS, format!("{e}，已使用内置设置项")),
+        };
 
         Self {
             page: Page::Pipeline,
             pipeline: Pipeline::new(),
             log_lines: Vec::new(),
+            log_phases: Vec::new(),
+            log_phases_seen: 0,
+            log_tracebacks: Vec::new(),
+            scroll_to_traceback: false,
+            log_saved: 0,
+            log_step: 0,
+            collapse_log_duplicates: true,
+            log_follow: true,
+            compact_mode: recent.compact_mode,
+            log_show_secrets: recent.log_show_secrets,
+            log_redactor: Redactor::default(),
+            log_opened_on_failure: false,
+            log_scroll_to_end: false,
+            log_wrap: recent.log_wrap,
+            log_font_size: recent.log_font_size.unwrap_or(DEFAULT_LOG_FONT_SIZE).clamp(MIN_LOG_FONT_SIZE, MAX_LOG_FONT_SIZE),
+            log_min_level: recent.log_min_level,
+            ui_scale,
+            ui_scale_applied: ui_scale,
+            ui_font_size,
             run_handle: None,
+            command_history: Vec::new(),
+            tts_engine: TtsEngine::Auto,
+            audio_format: recent.audio_format,
+            voice_mapping: recent.voice_mapping.clone(),
+            script_speakers: None,
+            script_structured: true,
+            script_scroll_to: None,
+            script_model: None,
+            selected_segments: (None, BTreeSet::new()),
+            audio_selected_only: false,
+            run_params: GenerationParams::default(),
             script_content: String::new(),
+            script_baseline: String::new(),
+            script_show_changes: false,
+            script_changes: None,
             script_dirty: false,
+            script_history: TextHistory::default(),
+            script_conflict: None,
+            script_autosave_path: None,
+            script_autosaved_at: Instant::now(),
+            script_autosave_offer: None,
+            episode_files: None,
+            editor_error: String::new(),
+            run_all: false,
+            run_all_skip_edit: true,
+            stop_after: vec![false; STEPS.len()],
+            work_dir_candidates: None,
+            work_dir_missing: None,
+            output_dir_writable: true,
+            publish_dry_run: false,
+            publish_upload_audio: false,
+            publish_preview: None,
+            draft_fields: None,
             settings,
-            settings_status: String::new(),
+            setting_groups,
+            settings_status,
+            toasts: Toasts::default(),
+            settings_focus: None,
+            scroll_to_focus: false,
+            confirm_settings_reload: false,
+            new_profile_name: String::new(),
+            env_changed_externally: false,
+            env_checked_at: Instant::now(),
+            recent_dirty: false,
+            recent_saved_at: Instant::now(),
+            pdf_probe: None,
+            pdf_error: None,
             last_pdf_dir: recent.last_pdf_dir,
             last_output_dir: recent.last_output_dir,
+            last_episode: recent.last_episode,
             project_root,
+            series_episode: recent
+                .series_id
+                .as_deref()
+                .and_then(|id| series.get(id))
+                .map_or(1, |s| s.next_episode()),
+            // Forget a series deleted from series.json in the meantime
+            series_id: recent.series_id.filter(|id| series.get(id).is_some()),
+            series,
+            new_series_name: String::new(),
+            series_error: String::new(),
+            running_step: None,
+            interrupted_run: recent.running_step,
+            confirm_exit: false,
+            confirm_publish: false,
+            confirm_regenerate: false,
+            regenerate_backup: true,
+            regenerate_keep_work_dir: true,
+            regenerate_error: String::new(),
+            exit_confirmed: false,
+            exit_stopping_since: None,
+            font_rx: Some(font_rx),
+            font_path: recent.font_path.clone(),
+            update_check: None,
         }
     }
 
-    fn setup_fonts(ctx: &egui::Context) {
-        let mut fonts = egui::FontDefinitions::default();
-
-        // Try common Chinese font paths on Windows
-        let font_paths = [
-            "C:/Windows/Fonts/msyh.ttc",    // Microsoft YaHei
-            "C:/Windows/Fonts/simhei.ttf",   // SimHei
-            "C:/Windows/Fonts/simsun.ttc",   // SimSun
-        ];
-
-        let mut loaded = false;
-        for path in &font_paths {
-            if let Ok(font_data) = std::fs::read(path) {
-                fonts.font_data.insert(
-                    "chinese".to_owned(),
-                    egui::FontData::from_owned(font_data).into(),
+    /// Version label plus the opt-in update check, laid out right to left.
+    fn draw_version_info(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new(format!("v{}", update::CURRENT_VERSION)).small().weak())
+            .on_hover_text("当前版本，反馈问题时请附上");
+        let ctx = ui.ctx().clone();
+        match self.update_check.as_mut().map(UpdateCheck::poll) {
+            None => {}
+            Some(None) => {
+                ui.spinner();
+                return;
+            }
+            Some(Some(Ok(Some(release)))) => {
+                ui.hyperlink_to(
+                    RichText::new(format!("有新版本 v{}", release.version)).color(Color32::from_rgb(34, 197, 94)),
+                    &release.url,
                 );
-
-                // Insert Chinese font as fallback for proportional and monospace
-                if let Some(family) = fonts.families.get_mut(&egui::FontFamily::Proportional) {
-                    family.push("chinese".to_owned());
-                }
-                if let Some(family) = fonts.families.get_mut(&egui::FontFamily::Monospace) {
-                    family.push("chinese".to_owned());
-                }
-
-                loaded = true;
-                break;
+                return;
+            }
+            Some(Some(Ok(None))) => {
+                ui.label(RichText::new("已是最新版本").small().weak());
+            }
+            Some(Some(Err(e))) => {
+                ui.label(RichText::new("检查失败").small().color(Color32::from_rgb(239, 68, 68)))
+                    .on_hover_text(e.as_str());
             }
         }
+        if ui
+            .small_button("检查更新")
+            .on_hover_text("联网获取最新发布版本（使用网络设置中的代理）")
+            .clicked()
+        {
+            self.update_check = Some(UpdateCheck::start(&ctx, &self.settings));
+        }
+    }
 
-        if !loaded {
-            eprintln!("Warning: no Chinese font found, CJK characters may not display correctly");
+    /// Remember the font the loader picked, once it reports back.
+    fn poll_font_loader(&mut self) {
+        let Some(rx) = &self.font_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(path) => {
+                if self.font_path.as_ref() != Some(&path) {
+                    self.font_path = Some(path);
+                    self.save_recent_paths();
+                }
+                self.font_rx = None;
+            }
+            Err(mpsc::TryRecvError::Disconnected) => self.font_rx = None,
+            Err(mpsc::TryRecvError::Empty) => {}
         }
+    }
 
-        ctx.set_fonts(fonts);
+    /// Mark the persisted state as changed; written by `flush_recent_paths`.
+    fn save_recent_paths(&mut self) {
+        self.recent_dirty = true;
     }
 
-    /// Save recent PDF/output directory paths to disk.
-    fn save_recent_paths(&self) {
+    /// Write `podcast-studio.json` if it changed, at most once per
+    /// `RECENT_SAVE_INTERVAL` unless `force` is set (on exit).
+    fn flush_recent_paths(&mut self, force: bool) {
+        if !self.recent_dirty || (!force && self.recent_saved_at.elapsed() < RECENT_SAVE_INTERVAL) {
+            return;
+        }
+        self.recent_dirty = false;
+        self.recent_saved_at = Instant::now();
         let recent = RecentPaths {
             last_pdf_dir: self.last_pdf_dir.clone(),
             last_output_dir: self.last_output_dir.clone(),
+            last_episode: self.last_episode.clone(),
+            active_profile: self.settings.profile.clone(),
+            voice_mapping: self.voice_mapping.clone(),
+            log_font_size: Some(self.log_font_size),
+            ui_scale: Some(self.ui_scale),
+            ui_font_size: Some(self.ui_font_size),
+            log_wrap: self.log_wrap,
+            log_min_level: self.log_min_level,
+            compact_mode: self.compact_mode,
+            log_show_secrets: self.log_show_secrets,
+            audio_format: self.audio_format,
+            font_path: self.font_path.clone(),
+            // An unanswered resume prompt is asked again next time
+            running_step: self.running_step.clone().or_else(|| self.interrupted_run.clone()),
+            series_id: self.series_id.clone(),
         };
         recent.save(&self.project_root);
     }
 
+    /// Keep the persisted running step in sync, written right away so it
+    /// survives a crash.
+    fn track_running_step(&mut self) {
+        let step = self.pipeline.current_step;
+        let running = (self.run_handle.is_some() && self.pipeline.steps[step] == StepStatus::Running)
+            .then(|| RunningStep {
+                step,
+                pdf_path: self.pipeline.pdf_path.clone(),
+                output_dir: self.pipeline.output_dir.clone(),
+                work_dir: self.pipeline.work_dir.clone(),
+            });
+        if running != self.running_step {
+            self.running_step = running;
+            self.save_recent_paths();
+            self.flush_recent_paths(true);
+        }
+    }
+
+    /// Restore the interrupted run's inputs and pick it up at its step: the
+    /// script and audio steps are spawned again, publishing waits for the user.
+    fn resume_interrupted_run(&mut self, run: RunningStep) {
+        let step = run.step.min(STEPS.len() - 1);
+        self.pipeline.pdf_path = run.pdf_path;
+        self.pipeline.output_dir = run.output_dir;
+        self.pipeline.work_dir = run.work_dir;
+        if step >= 2 {
+            self.forget_missing_work_dir();
+            if self.pipeline.work_dir.is_none() {
+                self.pipeline.current_step = 0;
+                return;
+            }
+        }
+        for i in 0..step {
+            self.pipeline.steps[i] = StepStatus::Done;
+        }
+        self.pipeline.current_step = step;
+        let missing = self.settings.missing_keys(STEPS[step].required_keys);
+        match step {
+            1 if self.pipeline.pdf_path.as_ref().is_some_and(|p| p.is_file()) && missing.is_empty() => {
+                self.start_script_generation();
+            }
+            3 if missing.is_empty() => self.start_audio_generation(),
+            _ => {}
+        }
+    }
+
+    /// Mark the current step done and move on, unless the user asked to stop here.
+    fn finish_step(&mut self) {
+        if self.stop_after[self.pipeline.current_step] {
+            self.pipeline.complete_current();
+        } else {
+            self.continue_to_next_step();
+        }
+    }
+
+    fn continue_to_next_step(&mut self) {
+        self.pipeline.advance();
+        if self.pipeline.current_step == 2 {
+            self.load_script();
+        }
+    }
+
     /// Poll the running subprocess for new log output.
     fn poll_subprocess(&mut self) {
+        // Lines from the previous frame, including a just-finished run
+        self.save_log_lines();
+
         if let Some(handle) = &mut self.run_handle {
-            // Drain available log lines
-            while let Ok(line) = handle.rx.try_recv() {
+            // Checked before draining, so a finished run's last lines are drained too
+            let finished = handle.is_finished();
+            // Drain available log lines, at most LOG_LINES_PER_FRAME so a burst
+            // can't stall one frame; the next frames (repainted while running) take the rest
+            let seen = self.log_lines.len();
+            let mut backlog = false;
+            loop {
+                if self.log_lines.len() - seen == LOG_LINES_PER_FRAME {
+                    backlog = true;
+                    break;
+                }
+                let Ok(mut line) = handle.rx.try_recv() else {
+                    break;
+                };
+                self.log_redactor.redact(&mut line);
+                match runner::parse_event(&line) {
+                    Some(PipelineEvent::Progress { pct }) => self.pipeline.progress = Some(pct.clamp(0.0, 1.0)),
+                    Some(PipelineEvent::Segment { done, total }) if total > 0 && done <= total => {
+                        self.pipeline.segment_progress = Some((done, total));
+                    }
+                    Some(PipelineEvent::Tokens { prompt, completion }) => {
+                        // The script step may call the LLM several times
+                        self.run_params.prompt_tokens += prompt;
+                        self.run_params.completion_tokens += completion;
+                    }
+                    Some(PipelineEvent::Phase { name }) => {
+                        self.log_phases.push(LogPhase { name, start: self.log_lines.len() });
+                    }
+                    // Read back from the log once the script step is done
+                    Some(PipelineEvent::WorkDir { .. } | PipelineEvent::Segment { .. }) | None => {}
+                }
                 self.log_lines.push(line);
             }
+            if self.log_lines.len() > seen {
+                self.log_tracebacks = runner::find_tracebacks(&self.log_lines);
+            }
+            // The run ends only once all of its output is in, which the handle would drop
+            if backlog {
+                return;
+            }
 
             // Check if process finished
+            if !finished {
+                return;
+            }
             if let Some(status) = handle.try_finish() {
+                self.episode_files = None;
+                if let Some(record) = self.command_history.last_mut() {
+                    record.status = if status.success() {
+                        StepStatus::Done
+                    } else {
+                        StepStatus::Failed(format!("退出码 {}", status.code().unwrap_or(-1)))
+                    };
+                }
+                // A preview run doesn't change the pipeline state
+                if std::mem::take(&mut self.publish_dry_run) {
+                    if status.success() {
+                        self.toasts.success("发布预览已生成");
+                    } else {
+                        self.toasts.error("发布预览失败");
+                    }
+                    self.publish_preview = Some(self.read_publish_preview(status.success()));
+                    self.run_handle = None;
+                    return;
+                }
+                let step_name = STEPS[self.pipeline.current_step].name;
                 if status.success() {
+                    self.toasts.success(format!("{step_name}完成"));
                     // Determine what to do based on current step
                     match self.pipeline.current_step {
                         1 => {
                             // Script generation done — extract work_dir from logs
                             self.extract_work_dir_from_logs();
-                            self.pipeline.advance();
-                            self.load_script();
+                            if let Some(dir) = &self.pipeline.work_dir {
+                                self.run_params.save(dir);
+                            }
+                            self.finish_step();
                         }
                         3 => {
                             // Audio generation done
-                            self.pipeline.advance();
+                            if let Some(dir) = &self.pipeline.work_dir {
+                                let mut params = GenerationParams::load(dir).unwrap_or_default();
+                                params.tts_engine = self.run_params.tts_engine.clone();
+                                params.save(dir);
+                            }
+                            // The selected lines are synthesized again now
+                            if self.audio_selected_only {
+                                self.selected_segments.1.clear();
+                                self.audio_selected_only = false;
+                            }
+                            self.finish_step();
                         }
                         4 => {
                             // Publish done
                             self.pipeline.complete_current();
+                            self.record_series_episode();
+                            if let Some(dir) = &self.pipeline.work_dir {
+                                if let Some(episode) = LastEpisode::from_work_dir(dir) {
+                                    self.last_episode = Some(episode);
+                                    self.save_recent_paths();
+                                }
+                            }
                         }
                         _ => {
-                            self.pipeline.advance();
+                            self.finish_step();
                         }
                     }
                 } else {
                     let code = status.code().unwrap_or(-1);
+                    self.toasts.error(format!("{step_name}失败 (退出码 {code})"));
                     self.pipeline.fail(format!("Process exited with code {code}"));
                 }
                 self.run_handle = None;
@@ -175,66 +863,274 @@ impl PodcastApp {
         }
     }
 
-    /// Try to extract work_dir path from log output.
-    fn extract_work_dir_from_logs(&mut self) {
-        for line in self.log_lines.iter().rev() {
-            if line.text.contains("Output dir:") {
-                if let Some(path_str) = line.text.split("Output dir:").nth(1) {
-                    let path = PathBuf::from(path_str.trim());
-                    if path.exists() {
-                        self.pipeline.work_dir = Some(path);
-                        return;
-                    }
-                }
+    /// Spawn the pipeline with the configured entrypoint and record it in the history.
+    fn spawn(&mut self, args: &[&str], envs: &[(&str, &str)]) -> RunHandle {
+        self.command_history.push(CommandRecord {
+            step: self.pipeline.current_step,
+            args: args.iter().map(|a| a.to_string()).collect(),
+            envs: envs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            started_at: format!("{} {}", chrono_today(), clock_now()),
+            status: StepStatus::Running,
+        });
+        let entrypoint = runner::Entrypoint::parse(self.settings.get("PYTHON_ENTRYPOINT"));
+        self.log_redactor = if self.log_show_secrets {
+            Redactor::default()
+        } else {
+            Redactor::new(self.settings.secret_values(self.setting_groups))
+        };
+        // Explicit per-run values win over the profile's
+        let mut all_envs = self.settings.profile_envs();
+        all_envs.extend_from_slice(envs);
+        runner::spawn_python(&self.project_root, &entrypoint, args, &all_envs)
+    }
+
+    fn reload_settings(&mut self) {
+        self.settings.reload();
+        self.env_changed_externally = false;
+        self.confirm_settings_reload = false;
+        self.settings_status.clear();
+        self.toasts.success("设置已重新加载");
+    }
+
+    /// Switch the active settings profile (`None` = plain `.env`).
+    fn switch_profile(&mut self, profile: Option<String>) {
+        let visible = std::mem::take(&mut self.settings.visible_secrets);
+        self.settings = Settings::load(&self.project_root, profile.as_deref());
+        self.settings.visible_secrets = visible;
+        self.env_changed_externally = false;
+        self.confirm_settings_reload = false;
+        self.settings_status.clear();
+        self.toasts.success(format!("已切换到配置: {}", profile_label(profile.as_deref())));
+        self.save_recent_paths();
+    }
+
+    fn clear_log(&mut self) {
+        self.save_log_lines();
+        self.log_lines.clear();
+        self.log_phases.clear();
+        self.log_phases_seen = 0;
+        self.log_tracebacks.clear();
+        self.log_opened_on_failure = false;
+        self.log_saved = 0;
+        self.log_step = self.pipeline.current_step;
+    }
+
+    /// Append unsaved log lines to `work_dir/logs/step-<n>.log`.
+    ///
+    /// Step 1 only learns its work dir when it finishes, so until then the
+    /// lines simply stay unsaved in `log_lines` and are flushed together.
+    fn save_log_lines(&mut self) {
+        let Some(dir) = &self.pipeline.work_dir else {
+            return;
+        };
+        if self.log_saved >= self.log_lines.len() {
+            return;
+        }
+        let logs_dir = dir.join("logs");
+        if std::fs::create_dir_all(&logs_dir).is_err() {
+            return;
+        }
+        let Ok(mut file) = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(logs_dir.join(format!("step-{}.log", self.log_step)))
+        else {
+            return;
+        };
+        let mut out = String::new();
+        if self.log_saved == 0 {
+            out.push_str(&format!("===== {} {} =====\n", chrono_today(), STEPS[self.log_step].name));
+        }
+        for line in &self.log_lines[self.log_saved..] {
+            out.push_str(&line.text);
+            out.push('\n');
+        }
+        if file.write_all(out.as_bytes()).is_ok() {
+            self.log_saved = self.log_lines.len();
+        }
+    }
+
+    fn read_publish_preview(&self, success: bool) -> Result<PublishPreview, String> {
+        if !success {
+            let unsupported = self.log_lines.iter().any(|l| l.text.contains("unrecognized arguments"));
+            return Err(if unsupported {
+                "当前 Python 管线不支持 --dry-run 预览，请更新后重试".to_string()
+            } else {
+                "预览失败，请查看日志".to_string()
+            });
+        }
+        let dir = self.pipeline.work_dir.as_deref().ok_or("未设置工作目录")?;
+        PublishPreview::load(dir)
+    }
+
+    /// Kill every running subprocess and mark its step as aborted.
+    fn abort_all(&mut self) {
+        if let Some(handle) = self.run_handle.take() {
+            handle.kill();
+            if !std::mem::take(&mut self.publish_dry_run) {
+                self.pipeline.fail("已中止".to_string());
             }
-            if line.text.contains("Script generation complete:") {
-                if let Some(path_str) = line.text.split("Script generation complete:").nth(1) {
-                    let path = PathBuf::from(path_str.trim());
-                    if path.exists() {
-                        self.pipeline.work_dir = Some(path);
-                        return;
-                    }
-                }
+            self.log_lines.push(LogLine::new("已中止运行中的任务".to_string(), true));
+            if let Some(record) = self.command_history.last_mut() {
+                record.status = StepStatus::Failed("已中止".to_string());
             }
         }
+    }
+
+    /// Try to extract work_dir path from log output.
+    fn extract_work_dir_from_logs(&mut self) {
+        if let Some(dir) = runner::work_dir_from_logs(&self.log_lines, &self.project_root) {
+            self.pipeline.work_dir = Some(dir);
+            return;
+        }
 
-        // Fallback: if we have pdf_path, construct expected work_dir
+        // Fallback: if we have pdf_path, look for the newest work_dir named after it
         if let Some(pdf) = &self.pipeline.pdf_path {
             let stem = pdf.file_stem().unwrap_or_default().to_string_lossy();
-            let today = chrono_today();
-            // Look for the directory
-            let expected = PathBuf::from("data/output/podcast").join(format!("{today}_{stem}"));
-            if expected.exists() {
-                self.pipeline.work_dir = Some(expected);
+            let prefix = work_dir_prefix(self.settings.get("WORKDIR_TEMPLATE"), &chrono_today(), &stem);
+            let found = find_work_dirs(&self.episode_roots())
+                .into_iter()
+                .find(|dir| dir.file_name().is_some_and(|n| n.to_string_lossy().starts_with(&prefix)));
+            if let Some(dir) = found {
+                self.pipeline.work_dir = Some(dir);
             }
         }
     }
 
+    /// Output folders episodes were written to: the chosen one, the last used
+    /// one and the last published episode's. Only when none is known, the
+    /// pipeline's own default, which is relative to the project root it runs in.
+    fn episode_roots(&self) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = Vec::new();
+        roots.extend(self.pipeline.output_dir.clone());
+        roots.extend(self.last_output_dir.clone());
+        roots.extend(self.last_episode.as_ref().and_then(|e| e.work_dir.parent()).map(Path::to_path_buf));
+        roots.dedup();
+        if roots.is_empty() {
+            roots.push(self.project_root.join("data").join("output").join("podcast"));
+        }
+        roots
+    }
+
     /// Load script.json content for editing.
     fn load_script(&mut self) {
+        // Segment engines and the like are read again along with the script
+        self.episode_files = None;
         if let Some(dir) = &self.pipeline.work_dir {
             let script_path = dir.join("script.json");
             if script_path.exists() {
                 match std::fs::read_to_string(&script_path) {
                     Ok(content) => {
+                        self.script_baseline = content.clone();
+                        self.script_history.reset(&content);
                         self.script_content = content;
+                        self.script_speakers = None;
                         self.script_dirty = false;
+                        self.script_conflict = None;
+                        self.check_autosave(&script_path);
                     }
                     Err(e) => {
                         self.script_content = format!("Error reading script.json: {e}");
+                        self.script_history.reset(&self.script_content);
                     }
                 }
             }
         }
     }
 
+    /// Offer autosaved edits newer than `script_path`; older or identical ones are stale.
+    fn check_autosave(&mut self, script_path: &Path) {
+        let autosave_path = script_path.with_file_name(SCRIPT_AUTOSAVE_FILE);
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let newer = match (modified(&autosave_path), modified(script_path)) {
+            (Some(autosaved), Some(saved)) => autosaved > saved,
+            (autosaved, _) => autosaved.is_some(),
+        };
+        self.script_autosave_offer = std::fs::read_to_string(&autosave_path)
+            .ok()
+            .filter(|content| newer && *content != self.script_content)
+            .map(|content| ScriptAutosave { content, show_diff: false });
+        if self.script_autosave_offer.is_none() {
+            let _ = std::fs::remove_file(&autosave_path);
+        }
+        self.script_autosave_path = Some(autosave_path);
+    }
+
+    /// Write unsaved script edits to the autosave sidecar every `SCRIPT_AUTOSAVE_SECONDS`.
+    /// The edits stay unsaved; only 保存 writes script.json.
+    fn autosave_script(&mut self, ctx: &egui::Context) {
+        if !self.script_dirty {
+            self.script_autosaved_at = Instant::now();
+            return;
+        }
+        let Some(path) = &self.script_autosave_path else {
+            return;
+        };
+        let secs = match self.settings.get("SCRIPT_AUTOSAVE_SECONDS").trim() {
+            "" => DEFAULT_AUTOSAVE_SECS,
+            value => value.parse().unwrap_or(DEFAULT_AUTOSAVE_SECS),
+        };
+        if secs == 0 {
+            return;
+        }
+        let interval = Duration::from_secs(secs);
+        let elapsed = self.script_autosaved_at.elapsed();
+        if elapsed < interval {
+            // No input may arrive before it's due
+            ctx.request_repaint_after(interval - elapsed);
+            return;
+        }
+        self.script_autosaved_at = Instant::now();
+        if let Err(e) = std::fs::write(path, &self.script_content) {
+            self.log_lines.push(LogLine::new(
+                format!("Failed to autosave script to {}: {e}", path.display()),
+                true,
+            ));
+        }
+    }
+
+    /// Delete the autosave sidecar once its edits are saved or discarded.
+    fn discard_autosave(&mut self) {
+        self.script_autosave_offer = None;
+        if let Some(path) = &self.script_autosave_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// Reload script.json, asking before discarding unsaved edits that conflict with disk.
+    fn reload_script(&mut self) {
+        let Some(dir) = &self.pipeline.work_dir else {
+            return;
+        };
+        let disk = match std::fs::read_to_string(dir.join("script.json")) {
+            Ok(content) => content,
+            Err(_) => return self.load_script(),
+        };
+        let changed_on_disk = disk != self.script_baseline;
+        if self.script_dirty && changed_on_disk && disk != self.script_content {
+            self.script_conflict = Some(ScriptConflict {
+                disk_content: disk,
+                show_diff: false,
+            });
+        } else {
+            self.load_script();
+        }
+    }
+
     /// Jump to any step. If jumping forward to step 2+, prompt for work_dir if missing.
     fn jump_to_step(&mut self, target: usize) {
         if target == self.pipeline.current_step {
             return;
         }
+        // Taking over manually ends an automatic run
+        if self.run_all {
+            self.stop_run_all();
+        }
 
         // Steps 2-4 need work_dir
+        if target >= 2 {
+            self.forget_missing_work_dir();
+        }
         if target >= 2 && self.pipeline.work_dir.is_none() {
             // Ask user to select the script.json file directly
             if let Some(file) = rfd::FileDialog::new()
@@ -269,19 +1165,48 @@ impl PodcastApp {
         }
     }
 
+    /// Drop a work dir that was deleted or moved, so steps 2-4 ask for another
+    /// instead of showing empty content.
+    fn forget_missing_work_dir(&mut self) {
+        let Some(dir) = &self.pipeline.work_dir else {
+            return;
+        };
+        if dir.is_dir() {
+            return;
+        }
+        self.toasts.error(format!("工作目录已不存在: {}", dir.display()));
+        self.work_dir_missing = self.pipeline.work_dir.take();
+        self.work_dir_candidates = None;
+        self.draft_fields = None;
+        self.publish_preview = None;
+        self.script_content.clear();
+        self.script_baseline.clear();
+        self.script_history.reset("");
+        self.script_dirty = false;
+        self.script_conflict = None;
+        self.script_autosave_path = None;
+        self.script_autosave_offer = None;
+        self.script_speakers = None;
+    }
+
     /// Save script.json back to disk.
     fn save_script(&mut self) {
         if let Some(dir) = &self.pipeline.work_dir {
             let script_path = dir.join("script.json");
             match std::fs::write(&script_path, &self.script_content) {
                 Ok(()) => {
+                    self.script_baseline = self.script_content.clone();
                     self.script_dirty = false;
+                    self.script_speakers = None;
+                    self.discard_autosave();
+                    self.toasts.success("剧本已保存");
                 }
                 Err(e) => {
-                    self.log_lines.push(LogLine {
-                        text: format!("Failed to save script.json: {e}"),
-                        is_stderr: true,
-                    });
+                    self.toasts.error(format!("保存 script.json 失败: {e}"));
+                    self.log_lines.push(LogLine::new(
+                        format!("Failed to save script.json: {e}"),
+                        true,
+                    ));
                 }
             }
         }
@@ -293,6 +1218,34 @@ impl PodcastApp {
 
         ui.add_space(8.0);
 
+        if self.pipeline.steps[step] == StepStatus::Queued {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("排队中，等待开始...");
+            });
+        }
+
+        // Auto-advancing steps can pause so the user can inspect the result
+        if step == 1 || step == 3 {
+            ui.checkbox(&mut self.stop_after[step], "完成后停止")
+                .on_hover_text("本步骤成功后不自动进入下一步");
+            if self.pipeline.steps[step] == StepStatus::Done && self.run_handle.is_none() {
+                ui.horizontal(|ui| {
+                    ui.colored_label(Color32::from_rgb(34, 197, 94), "本步骤已完成，已暂停。");
+                    if ui.button("继续下一步").clicked() {
+                        self.continue_to_next_step();
+                    }
+                    if ui.button("重新运行此步骤").clicked() {
+                        self.rerun_step(step);
+                    }
+                });
+            }
+            ui.add_space(4.0);
+        }
+
+        // Before the step itself, so its scroll areas fill whatever is left
+        self.draw_command_history(ui);
+
         match step {
             0 => self.draw_step_select_pdf(ui),
             1 => self.draw_step_generate_script(ui),
@@ -303,65 +1256,698 @@ impl PodcastApp {
         }
     }
 
-    // ── Step 0: Select PDF ──────────────────────────────────────
-
-    fn draw_step_select_pdf(&mut self, ui: &mut egui::Ui) {
-        // PDF selection
-        ui.horizontal(|ui| {
-            ui.label("PDF 文件:");
-            if let Some(path) = &self.pipeline.pdf_path {
-                ui.monospace(path.display().to_string());
-            } else {
-                ui.colored_label(Color32::from_rgb(156, 163, 175), "未选择");
-            }
-        });
-        if ui.button("选择 PDF 文件...").clicked() {
-            let mut dialog = rfd::FileDialog::new()
-                .add_filter("PDF", &["pdf"]);
-            if let Some(dir) = &self.last_pdf_dir {
-                dialog = dialog.set_directory(dir);
-            }
-            if let Some(path) = dialog.pick_file() {
-                if let Some(parent) = path.parent() {
-                    self.last_pdf_dir = Some(parent.to_path_buf());
-                }
-                self.pipeline.pdf_path = Some(path);
-                self.save_recent_paths();
-            }
+    /// Collapsible list of this step's earlier commands, each with a re-run button.
+    fn draw_command_history(&mut self, ui: &mut egui::Ui) {
+        let step = self.pipeline.current_step;
+        if !self.command_history.iter().any(|r| r.step == step) {
+            return;
         }
+        let can_run = self.run_handle.is_none() && !self.run_all;
+        let mut rerun = None;
+        egui::TopBottomPanel::bottom("command_history")
+            .frame(egui::Frame::NONE)
+            .show_separator_line(false)
+            .resizable(false)
+            .show_inside(ui, |ui| {
+                ui.add_space(8.0);
+                egui::CollapsingHeader::new("历史")
+                    .id_salt(("command_history", step))
+                    .show(ui, |ui| {
+                        for (i, record) in self.command_history.iter().enumerate().rev() {
+                            if record.step != step {
+                                continue;
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(&record.started_at).monospace().color(Color32::from_rgb(156, 163, 175)));
+                                match &record.status {
+                                    StepStatus::Done => {
+                                        ui.colored_label(Color32::from_rgb(34, 197, 94), "成功");
+                                    }
+                                    StepStatus::Failed(msg) => {
+                                        ui.colored_label(Color32::from_rgb(239, 68, 68), msg);
+                                    }
+                                    _ => {
+                                        ui.label("运行中");
+                                    }
+                                }
+                                if ui.add_enabled(can_run, egui::Button::new("再次运行").small()).clicked() {
+                                    rerun = Some(i);
+                                }
+                                let command = record.args.join(" ");
+                                let env_hint = record
+                                    .envs
+                                    .iter()
+                                    .map(|(k, v)| format!("{k}={v}"))
+                                    .collect::<Vec<_>>()
+                                    .join("\n");
+                                let label = ui.monospace(&command);
+                                if !env_hint.is_empty() {
+                                    label.on_hover_text(env_hint);
+                                }
+                            });
+                        }
+                    });
+            });
 
-        ui.add_space(12.0);
+        if let Some(i) = rerun {
+            self.rerun_command(i);
+        }
+    }
 
-        // Output directory selection
-        ui.horizontal(|ui| {
-            ui.label("保存位置:");
-            if let Some(dir) = &self.pipeline.output_dir {
-                ui.monospace(dir.display().to_string());
+    /// Spawn a history entry again with its exact args and per-run overrides.
+    fn rerun_command(&mut self, index: usize) {
+        let Some(record) = self.command_history.get(index) else {
+            return;
+        };
+        let args = record.args.clone();
+        let envs = record.envs.clone();
+        let dry_run = args.iter().any(|a| a == "--dry-run");
+        // A real publish still goes through the confirmation dialog
+        if record.step == 4 && !dry_run {
+            self.publish_upload_audio = args.iter().any(|a| a == "--upload-audio");
+            self.confirm_publish = true;
+            return;
+        }
+
+        self.clear_log();
+        if dry_run {
+            self.publish_preview = None;
+            self.publish_dry_run = true;
+        } else {
+            self.pipeline.set_running();
+            self.run_params.prompt_tokens = 0;
+            self.run_params.completion_tokens = 0;
+        }
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        let env_refs: Vec<(&str, &str)> = envs.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        self.run_handle = Some(self.spawn(&arg_refs, &env_refs));
+    }
+
+    // ── Step runs ───────────────────────────────────────────────
+
+    /// Spawn `podcast-script` for the selected PDF and output folder.
+    fn start_script_generation(&mut self) {
+        let (Some(pdf), Some(out)) = (&self.pipeline.pdf_path, &self.pipeline.output_dir) else {
+            return;
+        };
+        let (pdf_display, out_display) = (pdf.display().to_string(), out.display().to_string());
+        self.clear_log();
+        self.pipeline.set_running();
+        // Pass the LLM settings as arguments so the recorded params match the run
+        let model = self.settings.get("LLM_MODEL").trim().to_string();
+        let base_url = self.settings.get("LLM_BASE_URL").trim().to_string();
+        self.run_params = GenerationParams {
+            llm_model: model.clone(),
+            llm_host: pipeline::url_host(&base_url).to_string(),
+            ..Default::default()
+        };
+        let template = self.settings.get("WORKDIR_TEMPLATE").trim().to_string();
+        let mut args = vec![
+            "podcast-script", "--pdf", &pdf_display,
+            "--output-dir", &out_display,
+        ];
+        args.extend(pipeline::llm_args(&model, &base_url));
+        // Only pass --name-template when set, so older pipelines keep working
+        if !template.is_empty() {
+            args.extend(["--name-template", template.as_str()]);
+        }
+        self.run_handle = Some(self.spawn(&args, &[]));
+    }
+
+    /// Spawn `podcast-audio` for the work dir with the chosen engine and voices.
+    fn start_audio_generation(&mut self) {
+        let Some(dir) = self.pipeline.work_dir.clone() else {
+            return;
+        };
+        let dir_display = dir.display().to_string();
+        self.clear_log();
+        self.pipeline.set_running();
+        self.write_voice_mapping(&dir);
+        self.run_params.tts_engine = match self.tts_engine {
+            TtsEngine::Auto => self.settings.tts_engine_label(),
+            engine => engine.label().to_string(),
+        };
+        let mut envs: Vec<(&str, String)> = TTS_VOICE_KEYS
+            .iter()
+            .map(|key| (*key, self.settings.get(key).to_string()))
+            .filter(|(_, voice)| !voice.is_empty())
+            .collect();
+        // Per-run override; does not touch the saved settings
+        if let Some(backend) = self.tts_engine.backend() {
+            envs.push(("TTS_FORCE_BACKEND", backend.to_string()));
+        }
+        let envs: Vec<(&str, &str)> = envs.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let mut args = vec!["podcast-audio", "--dir", &dir_display];
+        // Only pass --format when needed, so older pipelines keep working for MP3
+        if self.audio_format != AudioFormat::Mp3 {
+            args.extend(["--format", self.audio_format.extension()]);
+        }
+        // Nothing selected falls back to a full run
+        let segments = if self.audio_selected_only && dir.join("segments").is_dir() {
+            let selected = self.segment_selection();
+            selected.iter().map(usize::to_string).collect::<Vec<_>>().join(",")
+        } else {
+            String::new()
+        };
+        if !segments.is_empty() {
+            args.extend(["--segments", segments.as_str()]);
+        }
+        self.run_handle = Some(self.spawn(&args, &envs));
+    }
+
+    /// Spawn `publish-podcast` to upload and create the WeChat draft.
+    fn start_publish(&mut self) {
+        let Some(dir) = &self.pipeline.work_dir else {
+            return;
+        };
+        let dir_display = dir.display().to_string();
+        if let Err(e) = self.save_draft_fields() {
+            self.pipeline.fail(e);
+            return;
+        }
+        self.clear_log();
+        self.publish_preview = None;
+        self.pipeline.set_running();
+        let series_args = self.series_args();
+        let mut args = vec!["publish-podcast", "--podcast-dir", &dir_display];
+        if self.publish_upload_audio {
+            args.push("--upload-audio");
+        }
+        args.extend(series_args.iter().map(String::as_str));
+        self.run_handle = Some(self.spawn(&args, &[]));
+    }
+
+    /// Read the work dir's files into `episode_files` unless they are already cached.
+    fn refresh_episode_files(&mut self) {
+        let Some(dir) = self.pipeline.work_dir.as_deref() else {
+            self.episode_files = None;
+            return;
+        };
+        if self.episode_files.as_ref().is_none_or(|files| files.work_dir != dir) {
+            self.episode_files = Some(EpisodeFiles::read(dir));
+        }
+    }
+
+    /// Whether the work dir's audio is known to be on the CDN. Episodes from
+    /// before `audio_uploaded` was recorded count as uploaded if they have a URL.
+    fn audio_uploaded(&mut self) -> bool {
+        self.refresh_episode_files();
+        let Some(meta) = self.episode_files.as_ref().and_then(|files| files.metadata.as_ref()) else {
+            return false;
+        };
+        metadata_audio_url(meta).is_some_and(|url| !url.is_empty())
+            && meta.get("audio_uploaded").and_then(|v| v.as_bool()) != Some(false)
+    }
+
+    /// Spawn `publish-podcast --dry-run`, which writes a preview and uploads nothing.
+    fn start_publish_preview(&mut self) {
+        let Some(dir) = &self.pipeline.work_dir else {
+            return;
+        };
+        let dir_display = dir.display().to_string();
+        self.clear_log();
+        if let Err(e) = self.save_draft_fields() {
+            self.publish_preview = Some(Err(e));
+            return;
+        }
+        self.publish_preview = None;
+        self.publish_dry_run = true;
+        // The series template changes the title, so the preview needs it too
+        let series_args = self.series_args();
+        let mut args = vec!["publish-podcast", "--podcast-dir", &dir_display, "--dry-run"];
+        args.extend(series_args.iter().map(String::as_str));
+        self.run_handle = Some(self.spawn(&args, &[]));
+    }
+
+    /// Write edited draft fields back before `publish-podcast` reads them.
+    fn save_draft_fields(&mut self) -> Result<(), String> {
+        self.episode_files = None;
+        match &self.draft_fields {
+            Some(fields) if Some(&fields.work_dir) == self.pipeline.work_dir.as_ref() => fields.save(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Queue steps 1-4 and let `drive_run_all` chain them.
+    fn start_run_all(&mut self) {
+        self.pipeline.advance();
+        for status in &mut self.pipeline.steps[1..] {
+            *status = StepStatus::Queued;
+        }
+        self.run_all = true;
+    }
+
+    /// Start the queued step of a run-all, or stop it once it has failed or finished.
+    fn drive_run_all(&mut self) {
+        if !self.run_all || self.run_handle.is_some() {
+            return;
+        }
+        let step = self.pipeline.current_step;
+        match &self.pipeline.steps[step] {
+            StepStatus::Queued => {
+                let missing = self.settings.missing_keys(STEPS[step].required_keys);
+                if !missing.is_empty() {
+                    self.pipeline.fail(format!("缺少配置: {}", missing.join(", ")));
+                    return;
+                }
+                match step {
+                    1 => self.start_script_generation(),
+                    2 if self.run_all_skip_edit => self.continue_to_next_step(),
+                    // Hand the script to the user; advancing queues the audio step
+                    2 => self.pipeline.steps[2] = StepStatus::Pending,
+                    3 => self.start_audio_generation(),
+                    4 => {
+                        self.publish_upload_audio = !self.audio_uploaded();
+                        self.confirm_publish = true;
+                    }
+                    _ => self.pipeline.steps[step] = StepStatus::Pending,
+                }
+            }
+            StepStatus::Failed(_) => self.stop_run_all(),
+            // A retry queues only the failed steps; hop over the finished ones between them
+            StepStatus::Done | StepStatus::Pending if !self.stop_after[step] => {
+                let next = (step + 1..STEPS.len()).find(|&i| self.pipeline.steps[i] == StepStatus::Queued);
+                match next {
+                    Some(next) if self.pipeline.steps[step].is_terminal() => self.pipeline.current_step = next,
+                    // Step 2 waits for the user while the rest is queued
+                    Some(_) => {}
+                    None => self.stop_run_all(),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Steps whose last run failed, in pipeline order.
+    fn failed_steps(&self) -> Vec<usize> {
+        (0..STEPS.len())
+            .filter(|&i| matches!(self.pipeline.steps[i], StepStatus::Failed(_)))
+            .collect()
+    }
+
+    /// Queue every failed step again and run them in order. Returns how many were queued.
+    fn retry_failed(&mut self) -> usize {
+        // Not while another step is queued or running
+        let in_flight = self.pipeline.steps.iter().any(|s| !s.is_terminal() && *s != StepStatus::Pending);
+        if in_flight {
+            return 0;
+        }
+        let failed = self.failed_steps();
+        let Some(&first) = failed.first() else {
+            return 0;
+        };
+        for &step in &failed {
+            self.pipeline.steps[step] = StepStatus::Queued;
+        }
+        self.pipeline.current_step = first;
+        if first == 2 {
+            self.load_script();
+        }
+        self.run_all = true;
+        failed.len()
+    }
+
+    /// End a run-all; steps that never started go back to pending.
+    fn stop_run_all(&mut self) {
+        self.run_all = false;
+        for status in &mut self.pipeline.steps {
+            if *status == StepStatus::Queued {
+                *status = StepStatus::Pending;
+            }
+        }
+    }
+
+    // ── Step 0: Select PDF ──────────────────────────────────────
+
+    fn draw_step_select_pdf(&mut self, ui: &mut egui::Ui) {
+        // PDF selection
+        let pdf_exists = self.pipeline.pdf_path.as_ref().is_some_and(|p| p.exists());
+        ui.horizontal(|ui| {
+            if let Some(path) = &self.pipeline.pdf_path {
+                path_row(ui, "PDF 文件:", path);
+                if pdf_exists && ui.small_button("定位文件").clicked() {
+                    runner::reveal_in_file_manager(path);
+                }
             } else {
+                ui.label("PDF 文件:");
                 ui.colored_label(Color32::from_rgb(156, 163, 175), "未选择");
             }
         });
+        if self.pipeline.pdf_path.is_some() && !pdf_exists {
+            ui.colored_label(
+                Color32::from_rgb(239, 68, 68),
+                "文件不存在，可能已被移动或删除，请重新选择 PDF。",
+            );
+        } else if let Some(e) = self.pdf_error.as_ref().filter(|_| self.pipeline.pdf_path.is_some()) {
+            ui.colored_label(Color32::from_rgb(239, 68, 68), format!("{e}，请重新选择 PDF。"));
+        } else if pdf_exists {
+            self.draw_pdf_preview(ui);
+        }
+        if ui.button("选择 PDF 文件...").clicked() {
+            let mut dialog = rfd::FileDialog::new()
+                .add_filter("PDF", &["pdf"]);
+            if let Some(dir) = &self.last_pdf_dir {
+                dialog = dialog.set_directory(dir);
+            }
+            if let Some(path) = dialog.pick_file() {
+                if let Some(parent) = path.parent() {
+                    self.last_pdf_dir = Some(parent.to_path_buf());
+                }
+                self.pdf_error = pdf::check_file(&path).err();
+                self.pipeline.pdf_path = Some(path);
+                self.save_recent_paths();
+            }
+        }
+
+        ui.add_space(12.0);
+
+        // Output directory selection
+        if let Some(dir) = &self.pipeline.output_dir {
+            path_row(ui, "保存位置:", dir);
+        } else {
+            ui.horizontal(|ui| {
+                ui.label("保存位置:");
+                ui.colored_label(Color32::from_rgb(156, 163, 175), "未选择");
+            });
+        }
         if ui.button("选择输出文件夹...").clicked() {
             let mut dialog = rfd::FileDialog::new();
             if let Some(dir) = &self.last_output_dir {
                 dialog = dialog.set_directory(dir);
             }
             if let Some(dir) = dialog.pick_folder() {
+                self.output_dir_writable = is_writable(&dir);
                 self.last_output_dir = Some(dir.clone());
                 self.pipeline.output_dir = Some(dir);
                 self.save_recent_paths();
             }
         }
+        if self.pipeline.output_dir.is_some() && !self.output_dir_writable {
+            ui.colored_label(
+                Color32::from_rgb(239, 68, 68),
+                "无法写入该文件夹，请检查权限或选择其他位置。",
+            );
+        }
+
+        self.draw_series_picker(ui);
 
         ui.add_space(16.0);
 
         // Next step (both must be selected)
-        let ready = self.pipeline.pdf_path.is_some() && self.pipeline.output_dir.is_some();
-        ui.add_enabled_ui(ready, |ui| {
-            if ui.button("下一步 →").clicked() {
-                self.pipeline.advance();
+        let ready = pdf_exists
+            && self.pdf_error.is_none()
+            && self.pipeline.output_dir.is_some()
+            && self.output_dir_writable;
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(ready, |ui| {
+                if ui.button("下一步 →").clicked() {
+                    self.pipeline.advance();
+                }
+                let missing: Vec<&str> = STEPS
+                    .iter()
+                    .flat_map(|step| self.settings.missing_keys(step.required_keys))
+                    .collect();
+                let run_all = ui
+                    .add_enabled(missing.is_empty(), egui::Button::new("一键生成全部"))
+                    .on_hover_text("依次生成剧本、音频并发布，遇到失败即停止")
+                    .on_disabled_hover_text(format!("缺少配置: {}", missing.join(", ")));
+                if run_all.clicked() {
+                    self.start_run_all();
+                }
+            });
+            ui.checkbox(&mut self.run_all_skip_edit, "跳过剧本编辑");
+        });
+
+        self.draw_last_episode(ui);
+    }
+
+    /// Series the episode is published into, with its episode number.
+    fn draw_series_picker(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(12.0);
+        let selected_name = self
+            .series_id
+            .as_deref()
+            .and_then(|id| self.series.get(id))
+            .map_or_else(|| "不属于系列".to_string(), |s| s.name.clone());
+        let mut picked = None;
+        ui.horizontal(|ui| {
+            ui.label("系列:");
+            egui::ComboBox::from_id_salt("series_picker")
+                .selected_text(selected_name)
+                .show_ui(ui, |ui| {
+                    if ui.selectable_label(self.series_id.is_none(), "不属于系列").clicked() {
+                        picked = Some(None);
+                    }
+                    for series in &self.series.series {
+                        let selected = self.series_id.as_ref() == Some(&series.id);
+                        if ui.selectable_label(selected, &series.name).clicked() {
+                            picked = Some(Some(series.id.clone()));
+                        }
+                    }
+                });
+            if self.series_id.is_some() {
+                ui.label("第");
+                ui.add(egui::DragValue::new(&mut self.series_episode).range(1..=9999))
+                    .on_hover_text("默认为该系列上次发布的期号 + 1，发布成功后自动递增");
+                ui.label("期");
             }
         });
+        if let Some(id) = picked {
+            self.select_series(id);
+        }
+        egui::CollapsingHeader::new("管理系列")
+            .id_salt("series_editor")
+            .show(ui, |ui| self.draw_series_editor(ui));
+    }
+
+    /// Publish into `id` (or no series), starting at its next episode number.
+    fn select_series(&mut self, id: Option<String>) {
+        self.series_episode = id
+            .as_deref()
+            .and_then(|id| self.series.get(id))
+            .map_or(1, |s| s.next_episode());
+        self.series_id = id;
+        self.save_recent_paths();
+    }
+
+    /// Edit, add and delete series; every change is written to `series.json` right away.
+    fn draw_series_editor(&mut self, ui: &mut egui::Ui) {
+        let selected = self.series_id.clone();
+        let mut changed = false;
+        let mut renumber = false;
+        let mut remove = None;
+        for (i, series) in self.series.series.iter_mut().enumerate() {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                egui::Grid::new(("series", i)).num_columns(2).show(ui, |ui| {
+                    ui.label("名称:");
+                    changed |= ui.text_edit_singleline(&mut series.name).changed();
+                    ui.end_row();
+                    ui.label("标题模板:");
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(&mut series.title_template).hint_text("{title}"))
+                        .on_hover_text(format!("可用占位符: {}", series::TITLE_PLACEHOLDERS))
+                        .changed();
+                    ui.end_row();
+                    ui.label("作者:");
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(&mut series.author).hint_text("飞行播客"))
+                        .on_hover_text("本期未填写作者时使用")
+                        .changed();
+                    ui.end_row();
+                    ui.label("封面:");
+                    ui.horizontal(|ui| {
+                        match &series.cover {
+                            Some(path) => {
+                                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                                ui.label(name).on_hover_text(path.display().to_string());
+                            }
+                            None => {
+                                ui.label(RichText::new("未设置").color(Color32::from_rgb(156, 163, 175)));
+                            }
+                        }
+                        if ui.small_button("选择...").on_hover_text("本期没有 cover.jpg 时使用").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().add_filter("图片", &["jpg", "jpeg", "png"]).pick_file() {
+                                series.cover = Some(path);
+                                changed = true;
+                            }
+                        }
+                        if series.cover.is_some() && ui.small_button("清除").clicked() {
+                            series.cover = None;
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+                    ui.label("上次期号:");
+                    if ui.add(egui::DragValue::new(&mut series.last_episode).range(0..=9999)).changed() {
+                        changed = true;
+                        renumber |= selected.as_ref() == Some(&series.id);
+                    }
+                    ui.end_row();
+                });
+                if ui.small_button("删除系列").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            let removed = self.series.series.remove(i);
+            if selected.as_ref() == Some(&removed.id) {
+                self.select_series(None);
+            }
+            changed = true;
+        }
+        if renumber {
+            self.select_series(selected);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_series_name)
+                    .hint_text("新系列名称")
+                    .desired_width(160.0),
+            );
+            let can_add = !self.new_series_name.trim().is_empty();
+            if ui.add_enabled(can_add, egui::Button::new("新建系列")).clicked() {
+                let id = self.series.add(&self.new_series_name);
+                self.new_series_name.clear();
+                self.select_series(Some(id));
+                changed = true;
+            }
+        });
+        if changed {
+            self.series_error = self.series.save(&self.project_root).err().unwrap_or_default();
+        }
+        if !self.series_error.is_empty() {
+            ui.colored_label(Color32::from_rgb(239, 68, 68), &self.series_error);
+        }
+    }
+
+    /// Count a published episode towards its series; the next one gets the following number.
+    fn record_series_episode(&mut self) {
+        let Some(id) = self.series_id.clone() else {
+            return;
+        };
+        // Only a created draft uses up the number, not just a clean exit
+        let published = self.pipeline.work_dir.as_deref().and_then(series::published_episode);
+        if published != Some((id.clone(), self.series_episode)) {
+            self.toasts.error("发布结果中没有本期草稿，系列期号未更新");
+            return;
+        }
+        if self.series.record_episode(&id, self.series_episode) {
+            if let Err(e) = self.series.save(&self.project_root) {
+                self.toasts.error(e);
+            }
+        }
+        self.select_series(Some(id));
+    }
+
+    /// `--series`/`--episode` for the publish step, empty outside a series.
+    fn series_args(&self) -> Vec<String> {
+        match self.series_id.as_deref().and_then(|id| self.series.get(id)) {
+            Some(series) => vec![
+                "--series".to_string(),
+                series.id.clone(),
+                "--episode".to_string(),
+                self.series_episode.to_string(),
+            ],
+            None => Vec::new(),
+        }
+    }
+
+    /// "上次制作" card with quick actions for the last published episode.
+    fn draw_last_episode(&mut self, ui: &mut egui::Ui) {
+        let Some(episode) = &self.last_episode else {
+            return;
+        };
+
+        ui.add_space(24.0);
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.label(RichText::new("上次制作").strong());
+            let title = if episode.title.is_empty() { "(无标题)" } else { episode.title.as_str() };
+            ui.label(RichText::new(title).size(15.0));
+            if !episode.date.is_empty() {
+                ui.label(
+                    RichText::new(&episode.date)
+                        .color(Color32::from_rgb(156, 163, 175))
+                        .size(12.0),
+                );
+            }
+            if !episode.cdn_url.is_empty() {
+                text_row(ui, "", &episode.cdn_url);
+            }
+            ui.horizontal(|ui| {
+                let mp3 = Path::new(&episode.mp3_path);
+                if ui.add_enabled(mp3.exists(), egui::Button::new("播放")).clicked() {
+                    runner::open_in_editor(mp3);
+                }
+                if ui
+                    .add_enabled(episode.work_dir.exists(), egui::Button::new("打开文件夹"))
+                    .clicked()
+                {
+                    runner::open_in_editor(&episode.work_dir);
+                }
+                if ui
+                    .add_enabled(!episode.cdn_url.is_empty(), egui::Button::new("复制链接"))
+                    .clicked()
+                {
+                    ui.ctx().copy_text(episode.cdn_url.clone());
+                }
+                if !episode.cdn_url.is_empty() {
+                    draw_link_formats(ui, &episode.title, &episode.cdn_url);
+                }
+            });
+        });
+    }
+
+    /// First-page text preview, warning when the PDF looks like a scan.
+    fn draw_pdf_preview(&mut self, ui: &mut egui::Ui) {
+        let Some(pdf_path) = &self.pipeline.pdf_path else {
+            return;
+        };
+        if self.pdf_probe.as_ref().is_none_or(|p| &p.path != pdf_path) {
+            self.pdf_probe = Some(PdfProbe::start(pdf_path));
+        }
+        let Some(probe) = &mut self.pdf_probe else {
+            return;
+        };
+
+        match probe.poll() {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("正在读取 PDF 文本...");
+                });
+                ui.ctx().request_repaint();
+            }
+            Some(Err(e)) => {
+                ui.colored_label(Color32::from_rgb(234, 179, 8), format!("无法预览 PDF 文本: {e}"));
+            }
+            Some(Ok(preview)) => {
+                ui.label(format!("共 {} 页", preview.page_count));
+                if preview.is_large() {
+                    ui.colored_label(
+                        Color32::from_rgb(234, 179, 8),
+                        "页数较多，生成剧本前的文本处理可能需要较长时间。",
+                    );
+                }
+                if preview.looks_image_only() {
+                    ui.colored_label(
+                        Color32::from_rgb(234, 179, 8),
+                        "⚠ 第一页几乎没有文本，可能是扫描版/图片 PDF，生成的剧本质量会很差。",
+                    );
+                } else {
+                    egui::CollapsingHeader::new("首页文本预览")
+                        .id_salt("pdf_preview")
+                        .show(ui, |ui| {
+                            ui.label(
+                                RichText::new(preview.excerpt())
+                                    .color(Color32::from_rgb(156, 163, 175))
+                                    .size(12.0),
+                            );
+                        });
+                }
+            }
+        }
     }
 
     // ── Step 1: Generate Script ─────────────────────────────────
@@ -373,91 +1959,661 @@ impl PodcastApp {
             let pdf_str = self.pipeline.pdf_path.as_ref().map(|p| p.display().to_string());
             let out_str = self.pipeline.output_dir.as_ref().map(|p| p.display().to_string());
             if let (Some(pdf_display), Some(out_display)) = (pdf_str, out_str) {
-                ui.label(format!("PDF: {pdf_display}"));
-                ui.label(format!("输出: {out_display}"));
+                text_row(ui, "PDF:", &pdf_display);
+                text_row(ui, "输出:", &out_display);
                 ui.add_space(8.0);
 
-                if ui.button("开始生成剧本").clicked() {
-                    self.log_lines.clear();
-                    self.pipeline.set_running();
-                    self.run_handle = Some(runner::spawn_python(&[
-                        "podcast-script", "--pdf", &pdf_display,
-                        "--output-dir", &out_display,
-                    ]));
+                self.draw_cost_estimate(ui);
+                if let Some(e) = &self.pdf_error {
+                    ui.colored_label(Color32::from_rgb(239, 68, 68), format!("{e}，请返回重新选择 PDF。"));
+                }
+                let can_run = self.draw_missing_settings(ui, 1) && self.pdf_error.is_none();
+                if ui.add_enabled(can_run, egui::Button::new("开始生成剧本")).clicked() {
+                    self.start_script_generation();
                 }
             } else {
                 ui.label("请先选择 PDF 文件和输出文件夹。");
             }
         }
 
-        // Show failed state with retry
-        if let StepStatus::Failed(ref msg) = self.pipeline.steps[1] {
-            ui.colored_label(Color32::from_rgb(239, 68, 68), format!("失败: {msg}"));
-            if ui.button("重试").clicked() {
-                self.pipeline.steps[1] = StepStatus::Pending;
+        if is_running {
+            // The pipeline is silent while reading the PDF, so show something
+            let pages = self.pdf_probe.as_ref().and_then(PdfProbe::page_count);
+            ui.horizontal(|ui| {
+                ui.spinner();
+                match pages {
+                    Some(n) => ui.label(format!("正在处理 PDF (共 {n} 页) 并生成剧本...")),
+                    None => ui.label("正在处理 PDF 并生成剧本..."),
+                };
+            });
+            if let Some(pct) = self.pipeline.progress {
+                ui.add(egui::ProgressBar::new(pct).show_percentage());
             }
         }
 
+        // Show failed state with retry
+        self.draw_failed_state(ui);
+
         self.draw_log_panel(ui);
     }
 
+    /// Ballpark input tokens and cost of generating a script from the selected PDF.
+    fn draw_cost_estimate(&mut self, ui: &mut egui::Ui) {
+        let Some(Ok(preview)) = self.pdf_probe.as_mut().and_then(|p| p.poll()) else {
+            return;
+        };
+        let tokens = preview.estimated_tokens();
+        if tokens == 0 {
+            return;
+        }
+        let mut text = format!("预计输入约 {} tokens", format_thousands(tokens));
+        match self.settings.llm_price_per_1k() {
+            Some(price) => text.push_str(&format!("，估算费用约 {:.2}", tokens as f64 / 1000.0 * price)),
+            None => text.push_str(" (在设置中填写模型价格可估算费用)"),
+        }
+        ui.label(RichText::new(text).color(Color32::from_rgb(156, 163, 175)).size(12.0))
+            .on_hover_text("按抽样页面的文字量估算，仅供参考；不含提示词和输出 tokens");
+        ui.add_space(4.0);
+    }
+
     // ── Step 2: Edit Script ─────────────────────────────────────
 
     fn draw_step_edit_script(&mut self, ui: &mut egui::Ui) {
         if let Some(dir) = self.pipeline.work_dir.clone() {
             let script_path = dir.join("script.json");
+            self.refresh_episode_files();
+            if let Some(params) = self.episode_files.as_ref().and_then(|files| files.generation.as_ref()) {
+                draw_token_usage(ui, params, self.settings.llm_price_per_1k());
+            }
 
+            let editor_command = self.settings.get("EDITOR_COMMAND").trim().to_string();
             ui.horizontal(|ui| {
-                if ui.button("在 VS Code 中打开").clicked() {
+                if !editor_command.is_empty() {
+                    if ui.button("在编辑器中打开").clicked() {
+                        self.editor_error = runner::open_with_command(&editor_command, &script_path)
+                            .err()
+                            .unwrap_or_default();
+                    }
+                } else if ui.button("在 VS Code 中打开").clicked() {
                     runner::open_in_vscode(&script_path);
                 }
                 if ui.button("用默认编辑器打开").clicked() {
                     runner::open_in_editor(&script_path);
                 }
                 if ui.button("重新加载").clicked() {
-                    self.load_script();
+                    self.reload_script();
+                }
+                if ui.button("导出为 Markdown/TXT").on_hover_text("导出便于阅读的对话稿").clicked() {
+                    self.export_script(&dir);
                 }
                 if self.script_dirty {
                     if ui.button("保存").clicked() {
                         self.save_script();
                     }
+                    ui.toggle_value(&mut self.script_show_changes, "查看更改")
+                        .on_hover_text("对比磁盘上的 script.json，确认要保存的修改");
                     ui.colored_label(Color32::from_rgb(234, 179, 8), "(未保存)");
                 }
             });
 
+            if !self.editor_error.is_empty() {
+                ui.colored_label(Color32::from_rgb(239, 68, 68), &self.editor_error);
+            }
+            if self.script_dirty && self.script_show_changes {
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.label(
+                        RichText::new("− 磁盘版本    + 当前修改")
+                            .color(Color32::from_rgb(156, 163, 175))
+                            .size(12.0),
+                    );
+                    draw_diff(ui, "script_changes", self.script_changes());
+                });
+            }
+
+            // Unknown speakers are caught here rather than as a failed audio step.
+            // The actions are laid out first, at the bottom, so the editor fills the rest
+            let unknown = self.unknown_speakers();
+            egui::TopBottomPanel::bottom("edit_script_actions")
+                .frame(egui::Frame::NONE)
+                .show_separator_line(false)
+                .resizable(false)
+                .show_inside(ui, |ui| {
+                    if !unknown.is_empty() {
+                        ui.add_space(4.0);
+                        ui.colored_label(
+                            Color32::from_rgb(234, 179, 8),
+                            format!("\u{26A0} 未知说话人: {}", unknown.join("、")),
+                        );
+                        ui.label(
+                            RichText::new(format!(
+                                "这些说话人没有对应的音色，生成音频时可能失败。请将 role 改为 {}，或在音频步骤中为其指定音色。",
+                                KNOWN_SPEAKERS.join(" / ")
+                            ))
+                            .small()
+                            .weak(),
+                        );
+                    }
+
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("← 重新生成剧本").clicked() {
+                            // Regenerating leaves hand edits behind in the old work dir
+                            if script_path.exists() {
+                                self.regenerate_error.clear();
+                                self.confirm_regenerate = true;
+                            } else {
+                                self.back_to_script_generation();
+                            }
+                        }
+                        let next_label = match (self.script_dirty, unknown.is_empty()) {
+                            (true, true) => "保存并继续 →",
+                            (false, true) => "下一步 →",
+                            (true, false) => "忽略警告，保存并继续 →",
+                            (false, false) => "忽略警告并继续 →",
+                        };
+                        if ui.button(next_label).clicked() {
+                            if self.script_dirty {
+                                self.save_script();
+                            }
+                            self.pipeline.advance();
+                        }
+                    });
+                });
+
+            self.draw_script_conflict(ui);
+            self.draw_autosave_offer(ui);
+            self.draw_work_dir_mismatch(ui, &dir);
+            if let Some(params) = self.episode_files.as_ref().and_then(|files| files.generation.as_ref()) {
+                draw_generation_params(ui, params);
+            }
+
             ui.add_space(8.0);
 
+            self.script_history.tick(&self.script_content);
+            let mut undo = false;
+            let mut redo = false;
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.script_structured, true, "结构化");
+                ui.selectable_value(&mut self.script_structured, false, "JSON");
+                if !self.script_structured {
+                    ui.separator();
+                    undo = ui
+                        .add_enabled(self.script_history.can_undo(), egui::Button::new("撤销").small())
+                        .on_hover_text("Ctrl+Z")
+                        .clicked();
+                    redo = ui
+                        .add_enabled(self.script_history.can_redo(), egui::Button::new("重做").small())
+                        .on_hover_text("Ctrl+Shift+Z")
+                        .clicked();
+                }
+            });
+
             // Inline editor
-            ScrollArea::vertical()
-                .max_height(ui.available_height() - 50.0)
-                .show(ui, |ui| {
-                    let response = ui.add(
-                        egui::TextEdit::multiline(&mut self.script_content)
-                            .code_editor()
-                            .desired_width(f32::INFINITY),
-                    );
-                    if response.changed() {
-                        self.script_dirty = true;
+            if self.script_structured {
+                self.draw_structured_script(ui);
+            } else {
+                let editor_id = egui::Id::new("raw_script_editor");
+                // Take the shortcuts before the TextEdit applies its own, shorter undo
+                if ui.memory(|m| m.has_focus(editor_id)) {
+                    redo |= ui.input_mut(|i| {
+                        i.consume_shortcut(&egui::KeyboardShortcut::new(
+                            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                            egui::Key::Z,
+                        )) || i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y))
+                    });
+                    undo |= ui.input_mut(|i| {
+                        i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z))
+                    });
+                }
+                let restored = (undo && self.script_history.undo(&mut self.script_content))
+                    || (redo && self.script_history.redo(&mut self.script_content));
+                if restored {
+                    self.script_dirty = self.script_content != self.script_baseline;
+                }
+                ScrollArea::vertical()
+                    .show(ui, |ui| {
+                        let response = ui.add(
+                            egui::TextEdit::multiline(&mut self.script_content)
+                                .id(editor_id)
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                        if response.changed() {
+                            self.script_dirty = true;
+                            self.script_history.edited();
+                        }
+                    });
+            }
+        } else {
+            self.draw_missing_work_dir(ui);
+        }
+    }
+
+    /// Write the script being edited as Markdown or plain text, by chosen extension.
+    fn export_script(&mut self, work_dir: &Path) {
+        let script = match Script::parse(&self.script_content) {
+            Ok(script) => script,
+            Err(e) => {
+                self.toasts.error(e);
+                return;
+            }
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("导出剧本")
+            .set_directory(work_dir)
+            .set_file_name("script.md")
+            .add_filter("Markdown", &["md"])
+            .add_filter("文本", &["txt"])
+            .save_file()
+        else {
+            return;
+        };
+        let markdown = path.extension().is_none_or(|ext| !ext.eq_ignore_ascii_case("txt"));
+        match std::fs::write(&path, script.to_readable(markdown)) {
+            Ok(()) => self.toasts.success(format!("已导出: {}", path.display())),
+            Err(e) => self.toasts.error(format!("导出失败: {e}")),
+        }
+    }
+
+    /// Return to step 1 so the script can be generated again.
+    fn back_to_script_generation(&mut self) {
+        self.pipeline.current_step = 1;
+        self.pipeline.steps[1] = StepStatus::Pending;
+        self.pipeline.steps[2] = StepStatus::Pending;
+    }
+
+    /// Ask before going back to regenerate when a script.json already exists.
+    fn draw_regenerate_confirm(&mut self, ctx: &egui::Context) {
+        let mut confirmed = false;
+        egui::Window::new("重新生成剧本")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("重新生成会写入新的工作目录（同名时追加 _2、_3 等后缀），现有的 script.json 不会被覆盖。");
+                if self.script_dirty {
+                    ui.colored_label(Color32::from_rgb(234, 179, 8), "编辑器中还有未保存的修改。");
+                }
+                ui.checkbox(&mut self.regenerate_backup, "先备份为 script.json.bak");
+                ui.checkbox(&mut self.regenerate_keep_work_dir, "保留当前工作目录")
+                    .on_hover_text("取消勾选则清空编辑器，后续步骤改用新生成的目录");
+                if !self.regenerate_error.is_empty() {
+                    ui.colored_label(Color32::from_rgb(239, 68, 68), &self.regenerate_error);
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("重新生成").clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        self.confirm_regenerate = false;
                     }
                 });
+            });
 
-            ui.add_space(8.0);
+        if !confirmed {
+            return;
+        }
+        if self.regenerate_backup {
+            if let Err(e) = self.backup_script() {
+                self.regenerate_error = format!("备份失败: {e}");
+                return;
+            }
+        }
+        self.confirm_regenerate = false;
+        if !self.regenerate_keep_work_dir {
+            self.pipeline.work_dir = None;
+            self.script_content.clear();
+            self.script_baseline.clear();
+            self.script_history.reset("");
+            self.script_dirty = false;
+            self.script_conflict = None;
+            self.script_autosave_path = None;
+            self.script_autosave_offer = None;
+            self.script_speakers = None;
+        }
+        self.back_to_script_generation();
+    }
+
+    /// Reset a completed step to Pending so it can run again, leaving the others alone.
+    fn rerun_step(&mut self, step: usize) {
+        if self.run_all {
+            self.stop_run_all();
+        }
+        let has_script = self
+            .pipeline
+            .work_dir
+            .as_ref()
+            .is_some_and(|dir| dir.join("script.json").exists());
+        if step == 1 && has_script {
+            // Regenerating overwrites script.json, so confirm first
+            self.regenerate_error.clear();
+            self.confirm_regenerate = true;
+        } else {
+            self.pipeline.steps[step] = StepStatus::Pending;
+        }
+    }
+
+    /// Copy the script to `script.json.bak`, including unsaved edits.
+    fn backup_script(&self) -> std::io::Result<()> {
+        let Some(dir) = &self.pipeline.work_dir else {
+            return Ok(());
+        };
+        let content = if self.script_dirty {
+            self.script_content.clone()
+        } else {
+            std::fs::read_to_string(dir.join("script.json"))?
+        };
+        settings::write_atomic(&dir.join("script.json.bak"), content.as_bytes())
+    }
+
+    /// Warn when the script landed outside the output folder the user picked.
+    fn draw_work_dir_mismatch(&self, ui: &mut egui::Ui, work_dir: &Path) {
+        let Some(output_dir) = &self.pipeline.output_dir else {
+            return;
+        };
+        if is_inside(work_dir, output_dir) {
+            return;
+        }
+        let actual = std::fs::canonicalize(work_dir).unwrap_or_else(|_| work_dir.to_path_buf());
+        egui::Frame::group(ui.style())
+            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(234, 179, 8)))
+            .show(ui, |ui| {
+                ui.colored_label(
+                    Color32::from_rgb(234, 179, 8),
+                    format!("⚠ 文件没有写到所选的输出文件夹 {}", output_dir.display()),
+                );
+                ui.horizontal(|ui| {
+                    ui.label(format!("实际位置: {}", actual.display()));
+                    if ui.button("打开实际目录").clicked() {
+                        runner::open_in_editor(&actual);
+                    }
+                });
+            });
+    }
+
+    /// Explain why there is no work dir and offer the ways to get one.
+    fn draw_missing_work_dir(&mut self, ui: &mut egui::Ui) {
+        if let Some(dir) = &self.work_dir_missing {
+            ui.colored_label(
+                Color32::from_rgb(239, 68, 68),
+                format!("工作目录已被删除或移动: {}", dir.display()),
+            );
+        }
+        ui.horizontal(|ui| {
+            ui.label("工作目录未找到，请从下面找到的剧本中选择，或");
+            if ui.button("← 重新生成剧本").clicked() {
+                self.back_to_script_generation();
+            }
+        });
+        self.draw_work_dir_recovery(ui);
+    }
+
+    /// Offer recently modified episode folders when the work dir is unknown.
+    fn draw_work_dir_recovery(&mut self, ui: &mut egui::Ui) {
+        if self.work_dir_candidates.is_none() {
+            self.work_dir_candidates = Some(find_work_dirs(&self.episode_roots()));
+        }
+        let candidates = self.work_dir_candidates.as_deref().unwrap_or_default();
+
+        let mut picked = None;
+        let mut rescan = false;
+        egui::Frame::group(ui.style()).show(ui, |ui| {
             ui.horizontal(|ui| {
-                if ui.button("← 重新生成剧本").clicked() {
-                    self.pipeline.current_step = 1;
-                    self.pipeline.steps[1] = StepStatus::Pending;
-                    self.pipeline.steps[2] = StepStatus::Pending;
-                }
-                let next_label = if self.script_dirty { "保存并继续 →" } else { "下一步 →" };
-                if ui.button(next_label).clicked() {
-                    if self.script_dirty {
-                        self.save_script();
+                ui.label(RichText::new("最近的剧本").strong());
+                rescan = ui.small_button("重新扫描").clicked();
+            });
+            if candidates.is_empty() {
+                ui.colored_label(Color32::from_rgb(156, 163, 175), "输出目录中没有找到 script.json");
+            }
+            for dir in candidates {
+                let name = dir.file_name().map_or_else(
+                    || dir.display().to_string(),
+                    |n| n.to_string_lossy().into_owned(),
+                );
+                if ui.button(name).on_hover_text(dir.display().to_string()).clicked() {
+                    picked = Some(dir.clone());
+                }
+            }
+        });
+
+        if rescan {
+            self.work_dir_candidates = None;
+        }
+        if let Some(dir) = picked {
+            self.pipeline.work_dir = Some(dir);
+            self.work_dir_candidates = None;
+            self.work_dir_missing = None;
+            if self.pipeline.current_step == 2 {
+                self.load_script();
+            }
+        }
+    }
+
+    /// Turns selected for synthesizing again in the current work dir.
+    fn segment_selection(&mut self) -> &mut BTreeSet<usize> {
+        let (dir, selected) = &mut self.selected_segments;
+        if *dir != self.pipeline.work_dir {
+            dir.clone_from(&self.pipeline.work_dir);
+            selected.clear();
+        }
+        selected
+    }
+
+    /// Re-parse `script_content` into `script_model` if it changed.
+    /// Unsaved edits against `script_baseline`, diffed again only once either side changes.
+    fn script_changes(&mut self) -> &[DiffLine<String>] {
+        let stale = self.script_changes.as_ref().is_none_or(|(baseline, content, _)| {
+            *baseline != self.script_baseline || *content != self.script_content
+        });
+        if stale {
+            let lines = diff::diff_lines(&self.script_baseline, &self.script_content)
+                .into_iter()
+                .map(DiffLine::into_owned)
+                .collect();
+            self.script_changes = Some((self.script_baseline.clone(), self.script_content.clone(), lines));
+        }
+        self.script_changes.as_ref().map_or(&[], |(_, _, lines)| lines)
+    }
+
+    fn refresh_script_model(&mut self) {
+        if self.script_model.as_ref().is_none_or(|(source, _)| *source != self.script_content) {
+            self.script_model = Some((self.script_content.clone(), Script::parse(&self.script_content)));
+        }
+    }
+
+    /// Speakers in the script being edited with neither a built-in nor an assigned voice.
+    fn unknown_speakers(&mut self) -> Vec<String> {
+        self.refresh_script_model();
+        let Some((_, Ok(script))) = &self.script_model else {
+            return Vec::new();
+        };
+        script
+            .speakers()
+            .into_iter()
+            .filter(|s| !KNOWN_SPEAKERS.contains(&s.as_str()) && !self.voice_mapping.contains_key(s))
+            .collect()
+    }
+
+    /// Turn-by-turn editor over the parsed script; edits are written back to `script_content`.
+    fn draw_structured_script(&mut self, ui: &mut egui::Ui) {
+        self.refresh_script_model();
+        self.refresh_episode_files();
+        self.segment_selection();
+        let selected = &mut self.selected_segments.1;
+        let Some((source, model)) = &mut self.script_model else {
+            return;
+        };
+        let script = match model {
+            Ok(script) => script,
+            Err(e) => {
+                ui.colored_label(Color32::from_rgb(239, 68, 68), format!("{e}，请在 JSON 视图中修复"));
+                return;
+            }
+        };
+
+        let speakers = script.speakers();
+        let outline = script.outline();
+        let engines = self.episode_files.as_ref().and_then(|files| files.engines.as_ref());
+        if let Some(engines) = engines {
+            ui.label(
+                RichText::new(format!("音频引擎: {}", engines.summary()))
+                    .color(Color32::from_rgb(156, 163, 175))
+                    .size(12.0),
+            )
+            .on_hover_text("上次生成音频时各引擎合成的片段数");
+        }
+        let no_engines = BTreeMap::new();
+        let by_turn = engines.map_or(&no_engines, |e| &e.by_turn);
+        if !selected.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!("已选 {} 句，可在生成音频时只重新合成这些句子", selected.len()))
+                        .color(Color32::from_rgb(156, 163, 175))
+                        .size(12.0),
+                );
+                if ui.small_button("清除选择").clicked() {
+                    selected.clear();
+                }
+            });
+        }
+        let height = ui.available_height();
+        let scroll_to = self.script_scroll_to.take();
+        let mut clicked = None;
+        let changed = ui
+            .horizontal_top(|ui| {
+                // A single section needs no navigation
+                if outline.len() > 1 {
+                    ui.vertical(|ui| {
+                        ui.set_width(OUTLINE_WIDTH);
+                        ui.label(RichText::new("大纲").strong());
+                        ScrollArea::vertical().id_salt("script_outline").max_height(height).show(ui, |ui| {
+                            for entry in &outline {
+                                let label = egui::Label::new(&entry.title).truncate().sense(egui::Sense::click());
+                                if ui.add(label).on_hover_text(&entry.title).clicked() {
+                                    clicked = Some(entry.first_turn);
+                                }
+                            }
+                        });
+                    });
+                    ui.separator();
+                }
+                ScrollArea::vertical()
+                    .id_salt("script_structured")
+                    .max_height(height)
+                    .show(ui, |ui| script_editor::draw_script_editor(ui, script, &speakers, scroll_to, by_turn, selected))
+                    .inner
+            })
+            .inner;
+        self.script_scroll_to = clicked;
+        if changed {
+            if let Ok(json) = serde_json::to_string_pretty(script) {
+                source.clone_from(&json);
+                self.script_content = json;
+                self.script_history.edited();
+                self.script_dirty = true;
+            }
+        }
+    }
+
+    /// Prompt shown when script.json changed on disk while there are unsaved edits.
+    fn draw_script_conflict(&mut self, ui: &mut egui::Ui) {
+        let Some(conflict) = &mut self.script_conflict else {
+            return;
+        };
+
+        let mut resolution = None;
+        egui::Frame::group(ui.style())
+            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(234, 179, 8)))
+            .show(ui, |ui| {
+                ui.colored_label(
+                    Color32::from_rgb(234, 179, 8),
+                    "script.json 已在外部被修改，且你有未保存的修改。",
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("保留我的修改").clicked() {
+                        resolution = Some(false);
+                    }
+                    if ui.button("使用磁盘版本").clicked() {
+                        resolution = Some(true);
+                    }
+                    let label = if conflict.show_diff { "隐藏差异" } else { "查看差异" };
+                    if ui.button(label).clicked() {
+                        conflict.show_diff = !conflict.show_diff;
+                    }
+                });
+                if conflict.show_diff {
+                    ui.label(
+                        RichText::new("− 我的修改    + 磁盘版本")
+                            .color(Color32::from_rgb(156, 163, 175))
+                            .size(12.0),
+                    );
+                    draw_diff(ui, "script_conflict", &diff::diff_lines(&self.script_content, &conflict.disk_content));
+                }
+            });
+
+        match resolution {
+            Some(true) => {
+                self.discard_autosave();
+                self.load_script();
+            }
+            Some(false) => {
+                // Treat the disk version as the new baseline so saving overwrites it knowingly
+                if let Some(conflict) = self.script_conflict.take() {
+                    self.script_baseline = conflict.disk_content;
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Prompt shown when an autosave left by unsaved edits is newer than script.json.
+    fn draw_autosave_offer(&mut self, ui: &mut egui::Ui) {
+        let Some(offer) = &mut self.script_autosave_offer else {
+            return;
+        };
+
+        let mut restore = None;
+        egui::Frame::group(ui.style())
+            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(234, 179, 8)))
+            .show(ui, |ui| {
+                ui.colored_label(
+                    Color32::from_rgb(234, 179, 8),
+                    "发现比 script.json 更新的自动保存，可能是上次未保存的修改。",
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("恢复").clicked() {
+                        restore = Some(true);
+                    }
+                    if ui.button("丢弃").clicked() {
+                        restore = Some(false);
                     }
-                    self.pipeline.advance();
+                    let label = if offer.show_diff { "隐藏差异" } else { "查看差异" };
+                    if ui.button(label).clicked() {
+                        offer.show_diff = !offer.show_diff;
+                    }
+                });
+                if offer.show_diff {
+                    ui.label(
+                        RichText::new("− 磁盘版本    + 自动保存")
+                            .color(Color32::from_rgb(156, 163, 175))
+                            .size(12.0),
+                    );
+                    draw_diff(ui, "script_autosave", &diff::diff_lines(&self.script_content, &offer.content));
                 }
             });
-        } else {
-            ui.label("工作目录未找到，请返回重新生成剧本。");
+
+        match restore {
+            Some(true) => {
+                // Restored as unsaved edits, undoable back to the disk version
+                if let Some(offer) = self.script_autosave_offer.take() {
+                    self.script_content = offer.content;
+                    self.script_history.edited();
+                    self.script_dirty = true;
+                }
+            }
+            Some(false) => self.discard_autosave(),
+            None => {}
         }
     }
 
@@ -466,81 +2622,484 @@ impl PodcastApp {
     fn draw_step_generate_audio(&mut self, ui: &mut egui::Ui) {
         let is_running = self.run_handle.is_some();
 
+        if self.pipeline.work_dir.is_none() && !is_running {
+            self.draw_missing_work_dir(ui);
+        }
+
         if !is_running && self.pipeline.steps[3] == StepStatus::Pending {
             let dir_str = self.pipeline.work_dir.as_ref().map(|d| d.display().to_string());
             if let Some(dir_display) = dir_str {
-                ui.label(format!("工作目录: {dir_display}"));
+                text_row(ui, "工作目录:", &dir_display);
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("本次 TTS 引擎:");
+                    for engine in TtsEngine::ALL {
+                        ui.selectable_value(&mut self.tts_engine, engine, engine.label());
+                    }
+                });
+                if self.tts_engine == TtsEngine::Auto {
+                    ui.label(
+                        RichText::new(format!("按设置: Qwen → {}", self.settings.tts_engine_label()))
+                            .color(Color32::from_rgb(156, 163, 175))
+                            .size(12.0),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    ui.label("输出格式:");
+                    for format in AudioFormat::ALL {
+                        if ui.selectable_value(&mut self.audio_format, format, format.label()).changed() {
+                            self.save_recent_paths();
+                        }
+                    }
+                });
+                self.draw_voice_mapping(ui);
+                let selected = self.segment_selection().len();
+                let has_segments = self.pipeline.work_dir.as_ref().is_some_and(|d| d.join("segments").is_dir());
+                let can_select = selected > 0 && has_segments;
+                let response = ui.add_enabled(
+                    can_select,
+                    egui::Checkbox::new(&mut self.audio_selected_only, format!("仅合成选中片段 ({selected} 句)")),
+                );
+                if !has_segments {
+                    response.on_disabled_hover_text("还没有生成过音频，需要完整合成一次");
+                } else {
+                    response
+                        .on_hover_text("其余句子沿用已有音频，再重新拼接整期节目")
+                        .on_disabled_hover_text("在剧本编辑的结构化视图中勾选要重新合成的句子");
+                }
                 ui.add_space(8.0);
 
-                if ui.button("开始合成音频").clicked() {
-                    self.log_lines.clear();
-                    self.pipeline.set_running();
-                    self.run_handle = Some(runner::spawn_python(&[
-                        "podcast-audio", "--dir", &dir_display,
-                    ]));
+                let can_run = self.draw_missing_settings(ui, 3);
+                if ui.add_enabled(can_run, egui::Button::new("开始合成音频")).clicked() {
+                    self.start_audio_generation();
                 }
             }
         }
 
-        if let StepStatus::Failed(ref msg) = self.pipeline.steps[3] {
-            ui.colored_label(Color32::from_rgb(239, 68, 68), format!("失败: {msg}"));
-            if ui.button("重试").clicked() {
-                self.pipeline.steps[3] = StepStatus::Pending;
+        if is_running {
+            if let Some((done, total)) = self.pipeline.segment_progress {
+                ui.label(format!("片段 {done}/{total}"));
+                ui.add(egui::ProgressBar::new(done as f32 / total as f32).show_percentage());
+            } else if let Some(pct) = self.pipeline.progress {
+                ui.add(egui::ProgressBar::new(pct).show_percentage().animate(true));
+            } else {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("正在合成音频...");
+                });
+            }
+        }
+
+        if !is_running && self.pipeline.steps[3] == StepStatus::Done {
+            self.refresh_episode_files();
+            if let Some(files) = &self.episode_files {
+                draw_audio_summary(ui, files);
             }
         }
 
+        self.draw_failed_state(ui);
+
         self.draw_log_panel(ui);
     }
 
+    /// Speakers in the current script, re-read when the work dir or script changes.
+    fn current_speakers(&mut self) -> Vec<String> {
+        let Some(dir) = &self.pipeline.work_dir else {
+            return Vec::new();
+        };
+        if self.script_speakers.as_ref().is_none_or(|(cached, _)| cached != dir) {
+            let speakers = std::fs::read_to_string(dir.join("script.json"))
+                .ok()
+                .and_then(|content| Script::parse(&content).ok())
+                .map(|script| script.speakers())
+                .unwrap_or_default();
+            self.script_speakers = Some((dir.clone(), speakers));
+        }
+        self.script_speakers.as_ref().map(|(_, s)| s.clone()).unwrap_or_default()
+    }
+
+    /// Per-speaker voice dropdowns for the audio step.
+    fn draw_voice_mapping(&mut self, ui: &mut egui::Ui) {
+        let speakers = self.current_speakers();
+        if speakers.is_empty() {
+            return;
+        }
+
+        let mut changed = false;
+        egui::CollapsingHeader::new("说话人音色")
+            .id_salt("voice_mapping")
+            .show(ui, |ui| {
+                ui.label(
+                    RichText::new("用于 DashScope 与 Qwen 云端音色；Edge 和本地模型使用各自的音色")
+                        .color(Color32::from_rgb(156, 163, 175))
+                        .size(12.0),
+                );
+                egui::Grid::new("voice_mapping_grid")
+                    .num_columns(2)
+                    .spacing([8.0, 6.0])
+                    .show(ui, |ui| {
+                        for speaker in &speakers {
+                            ui.label(speaker);
+                            let current = self.voice_mapping.get(speaker).cloned().unwrap_or_default();
+                            let shown = if current.is_empty() { "默认" } else { current.as_str() };
+                            egui::ComboBox::from_id_salt(("voice_for", speaker))
+                                .selected_text(shown)
+                                .show_ui(ui, |ui| {
+                                    if ui.selectable_label(current.is_empty(), "默认").clicked() {
+                                        self.voice_mapping.remove(speaker);
+                                        changed = true;
+                                    }
+                                    for voice in TTS_VOICES {
+                                        if ui.selectable_label(current == *voice, *voice).clicked() {
+                                            self.voice_mapping.insert(speaker.clone(), voice.to_string());
+                                            changed = true;
+                                        }
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+            });
+        if changed {
+            self.save_recent_paths();
+        }
+    }
+
+    /// Write `voices.json` for the speakers in this script that have an assigned voice.
+    fn write_voice_mapping(&mut self, work_dir: &Path) {
+        let speakers = self.current_speakers();
+        let mapping: BTreeMap<&String, &String> = self
+            .voice_mapping
+            .iter()
+            .filter(|(speaker, _)| speakers.contains(speaker))
+            .collect();
+        let path = work_dir.join("voices.json");
+        if mapping.is_empty() {
+            let _ = std::fs::remove_file(path);
+        } else if let Ok(json) = serde_json::to_string_pretty(&mapping) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
     // ── Step 4: Publish ─────────────────────────────────────────
 
     fn draw_step_publish(&mut self, ui: &mut egui::Ui) {
         let is_running = self.run_handle.is_some();
 
+        if self.pipeline.work_dir.is_none() && !is_running {
+            self.draw_missing_work_dir(ui);
+        }
+
         if self.pipeline.steps[4] == StepStatus::Done {
-            ui.colored_label(
-                Color32::from_rgb(34, 197, 94),
-                "发布完成！草稿已创建。",
-            );
-        } else if !is_running && self.pipeline.steps[4] == StepStatus::Pending {
-            let dir_str = self.pipeline.work_dir.as_ref().map(|d| d.display().to_string());
-            if let Some(dir_display) = &dir_str {
-                // Show MP3 path if exists
-                if let Some(dir) = &self.pipeline.work_dir {
-                    let meta_path = dir.join("metadata.json");
-                    if meta_path.exists() {
-                        if let Ok(content) = std::fs::read_to_string(&meta_path) {
-                            if let Ok(meta) = serde_json::from_str::<serde_json::Value>(&content) {
-                                if let Some(mp3) = meta.get("mp3_path").and_then(|v| v.as_str()) {
-                                    ui.label(format!("MP3: {mp3}"));
-                                }
-                                if let Some(url) = meta.get("mp3_cdn_url").and_then(|v| v.as_str()) {
-                                    ui.label(format!("CDN: {url}"));
-                                }
-                            }
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    Color32::from_rgb(34, 197, 94),
+                    "发布完成！草稿已创建。",
+                );
+                if !is_running && ui.button("重新运行此步骤").on_hover_text("再次发布会创建新的草稿").clicked() {
+                    self.rerun_step(4);
+                }
+            });
+            self.refresh_episode_files();
+            let files = self.episode_files.as_ref();
+            if let Some((files, meta)) = files.and_then(|files| Some((files, files.metadata.as_ref()?))) {
+                let dir = &files.work_dir;
+                if let Some(url) = metadata_audio_url(meta) {
+                    ui.horizontal(|ui| {
+                        text_row(ui, "CDN:", url);
+                        draw_link_formats(ui, metadata_title(meta, dir), url);
+                    });
+                }
+                ui.horizontal(|ui| {
+                    let audio = files.audio.as_ref().and(metadata_audio_path(meta)).map(Path::new);
+                    if ui
+                        .add_enabled(audio.is_some(), egui::Button::new("播放音频"))
+                        .on_hover_text("用系统默认播放器打开")
+                        .on_disabled_hover_text("metadata.json 中没有音频文件，或文件已不存在")
+                        .clicked()
+                    {
+                        if let Some(audio) = audio {
+                            runner::open_in_editor(audio);
+                        }
+                    }
+                    if ui.button("打开所在文件夹").clicked() {
+                        match audio {
+                            Some(audio) => runner::reveal_in_file_manager(audio),
+                            None => runner::open_in_editor(dir),
                         }
                     }
+                });
+            }
+        } else if !is_running && self.pipeline.steps[4] == StepStatus::Pending {
+            self.refresh_episode_files();
+            if let Some(files) = &self.episode_files {
+                // Show audio path if exists
+                if let Some(params) = &files.generation {
+                    draw_generation_params(ui, params);
+                }
+                if let Some(meta) = &files.metadata {
+                    if let Some(path) = metadata_audio_path(meta) {
+                        text_row(ui, "音频:", path);
+                        draw_audio_info(ui, files.audio.as_ref());
+                    }
+                    if let Some(url) = metadata_audio_url(meta) {
+                        ui.horizontal(|ui| {
+                            text_row(ui, "CDN:", url);
+                            draw_link_formats(ui, metadata_title(meta, &files.work_dir), url);
+                        });
+                    }
+                    draw_chapter_timestamps(ui, meta);
                 }
 
-                ui.add_space(8.0);
-                if ui.button("上传并创建微信草稿").clicked() {
-                    self.log_lines.clear();
-                    self.pipeline.set_running();
-                    self.run_handle = Some(runner::spawn_python(&[
-                        "publish-podcast", "--podcast-dir", dir_display,
-                    ]));
+                // An earlier, possibly cancelled, run may have uploaded the audio already
+                let uploaded = self.audio_uploaded();
+                if uploaded {
+                    ui.colored_label(Color32::from_rgb(34, 197, 94), "✔ 音频已上传，可直接创建草稿");
+                } else {
+                    ui.colored_label(Color32::from_rgb(234, 179, 8), "音频尚未上传，或上次上传没有完成");
                 }
+
+                ui.add_space(8.0);
+                let title_ok = self.draw_draft_fields(ui);
+                ui.add_space(8.0);
+                let can_run = self.draw_missing_settings(ui, 4) && title_ok;
+                ui.horizontal(|ui| {
+                    if uploaded {
+                        if ui
+                            .add_enabled(can_run, egui::Button::new("仅创建草稿(已上传)"))
+                            .on_hover_text("沿用已上传的音频和封面")
+                            .clicked()
+                        {
+                            self.publish_upload_audio = false;
+                            self.confirm_publish = true;
+                        }
+                        if ui.add_enabled(can_run, egui::Button::new("重新上传并发布")).clicked() {
+                            self.publish_upload_audio = true;
+                            self.confirm_publish = true;
+                        }
+                    } else if ui.add_enabled(can_run, egui::Button::new("上传并创建微信草稿")).clicked() {
+                        self.publish_upload_audio = true;
+                        self.confirm_publish = true;
+                    }
+                    if ui.button("预览(不发布)").on_hover_text("生成将要上传的内容，不创建草稿").clicked() {
+                        self.start_publish_preview();
+                    }
+                });
             }
         }
 
-        if let StepStatus::Failed(ref msg) = self.pipeline.steps[4] {
+        if is_running && self.publish_dry_run {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("正在生成发布预览...");
+            });
+        }
+        self.draw_publish_preview(ui);
+
+        self.draw_failed_state(ui);
+
+        self.draw_log_panel(ui);
+    }
+
+    /// Editable title/digest/author of the draft. Returns false if the title
+    /// can't be published as is.
+    fn draw_draft_fields(&mut self, ui: &mut egui::Ui) -> bool {
+        let Some(dir) = &self.pipeline.work_dir else {
+            return true;
+        };
+        if self.draft_fields.as_ref().is_none_or(|f| &f.work_dir != dir) {
+            self.draft_fields = Some(DraftFields::load(dir));
+        }
+        let Some(fields) = &mut self.draft_fields else {
+            return true;
+        };
+
+        let title_len = fields.title.trim().chars().count();
+        let digest_len = fields.digest.trim().chars().count();
+        egui::Grid::new("draft_fields").num_columns(3).spacing([8.0, 4.0]).show(ui, |ui| {
+            ui.label("标题");
+            ui.add(egui::TextEdit::singleline(&mut fields.title).desired_width(360.0));
+            let color = if title_len > WECHAT_TITLE_MAX || title_len == 0 {
+                Color32::from_rgb(239, 68, 68)
+            } else {
+                Color32::from_rgb(156, 163, 175)
+            };
+            ui.label(RichText::new(format!("{title_len}/{WECHAT_TITLE_MAX}")).color(color).size(12.0));
+            ui.end_row();
+
+            ui.label("摘要");
+            ui.add(
+                egui::TextEdit::multiline(&mut fields.digest)
+                    .desired_rows(2)
+                    .desired_width(360.0)
+                    .hint_text("留空则使用标题"),
+            );
+            let color = if digest_len > WECHAT_DIGEST_MAX {
+                Color32::from_rgb(234, 179, 8)
+            } else {
+                Color32::from_rgb(156, 163, 175)
+            };
+            ui.label(RichText::new(format!("{digest_len}/{WECHAT_DIGEST_MAX}")).color(color).size(12.0));
+            ui.end_row();
+
+            ui.label("作者");
+            ui.add(egui::TextEdit::singleline(&mut fields.author).desired_width(360.0).hint_text("飞行播客"));
+            ui.end_row();
+        });
+
+        if title_len == 0 {
+            ui.colored_label(Color32::from_rgb(239, 68, 68), "标题不能为空");
+        } else if title_len > WECHAT_TITLE_MAX {
+            ui.colored_label(
+                Color32::from_rgb(239, 68, 68),
+                format!("标题超过微信限制 ({WECHAT_TITLE_MAX} 字)，创建草稿会失败"),
+            );
+        }
+        if digest_len > WECHAT_DIGEST_MAX {
+            ui.colored_label(
+                Color32::from_rgb(234, 179, 8),
+                format!("摘要超过 {WECHAT_DIGEST_MAX} 字，发布时将被截断"),
+            );
+        }
+        (1..=WECHAT_TITLE_MAX).contains(&title_len)
+    }
+
+    // ── Settings precondition (shared by steps 1, 3, 4) ─────────
+
+    /// Show missing required settings for a step. Returns true when the step can run.
+    fn draw_missing_settings(&mut self, ui: &mut egui::Ui, step: usize) -> bool {
+        let missing = self.settings.missing_keys(STEPS[step].required_keys);
+        if missing.is_empty() {
+            return true;
+        }
+        egui::Frame::group(ui.style())
+            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(239, 68, 68)))
+            .show(ui, |ui| {
+                ui.colored_label(
+                    Color32::from_rgb(239, 68, 68),
+                    format!("无法开始「{}」: 以下配置为空", STEPS[step].name),
+                );
+                for key in &missing {
+                    let label = self
+                        .setting_groups
+                        .iter()
+                        .flat_map(|(_, fields)| fields.iter())
+                        .find(|f| f.key == *key)
+                        .map(|f| f.label);
+                    match label {
+                        Some(label) => ui.label(format!("• {label} ({key})")),
+                        None => ui.label(format!("• {key}")),
+                    };
+                }
+                if ui.button("前往设置").clicked() {
+                    self.page = Page::Settings;
+                }
+            });
+        ui.add_space(4.0);
+        false
+    }
+
+    // ── Failed state (shared by steps 1, 3, 4) ──────────────────
+
+    fn draw_failed_state(&mut self, ui: &mut egui::Ui) {
+        if !self.pipeline.can_retry() {
+            return;
+        }
+        let step = self.pipeline.current_step;
+        let attempts = self.pipeline.attempts[step];
+        let errors = &self.pipeline.errors[step];
+
+        if let StepStatus::Failed(ref msg) = self.pipeline.steps[step] {
             ui.colored_label(Color32::from_rgb(239, 68, 68), format!("失败: {msg}"));
+        }
+        if attempts > 1 {
+            ui.label(format!("第 {attempts} 次尝试"));
+        }
+        // The last entry is the current failure shown above
+        if errors.len() > 1 {
+            egui::CollapsingHeader::new(format!("之前的错误 ({})", errors.len() - 1))
+                .id_salt(("previous_errors", step))
+                .show(ui, |ui| {
+                    for (i, err) in errors[..errors.len() - 1].iter().enumerate() {
+                        ui.label(
+                            RichText::new(format!("第 {} 次: {err}", i + 1))
+                                .color(Color32::from_rgb(156, 163, 175)),
+                        );
+                    }
+                });
+        }
+        ui.horizontal(|ui| {
             if ui.button("重试").clicked() {
-                self.pipeline.steps[4] = StepStatus::Pending;
+                self.pipeline.steps[step] = StepStatus::Pending;
+            }
+            if ui
+                .button("复制错误摘要")
+                .on_hover_text("复制步骤、错误、最近日志、设置 (不含密钥) 和系统信息，便于提交问题")
+                .clicked()
+            {
+                ui.ctx().copy_text(self.error_summary(step));
+                self.toasts.success("已复制错误摘要");
+            }
+        });
+        // The exception line is the most useful single line of a traceback
+        if let Some(line) = self.log_tracebacks.last().and_then(|tb| self.log_lines.get(tb.end - 1)) {
+            ui.label(
+                RichText::new(format!("Python 异常: {}", line.text.trim()))
+                    .monospace()
+                    .color(Color32::from_rgb(239, 68, 68)),
+            );
+        }
+        if let Some(hint) = runner::detect_error_hint(step, &self.log_lines) {
+            if ui.link(RichText::new(hint.message).color(Color32::from_rgb(234, 179, 8))).clicked() {
+                self.page = Page::Settings;
+                self.settings_focus = Some(hint.key);
+                self.scroll_to_focus = true;
             }
         }
+    }
 
-        self.draw_log_panel(ui);
+    /// Plain-text report of the failed `step` for bug reports. Secret settings
+    /// only say whether they are set, and the log lines are masked again in
+    /// case "日志显示密钥" is on.
+    fn error_summary(&self, step: usize) -> String {
+        const LOG_TAIL: usize = 20;
+        let error = match &self.pipeline.steps[step] {
+            StepStatus::Failed(msg) => msg.as_str(),
+            _ => "",
+        };
+        let mut out = String::from("## 错误摘要\n\n");
+        out.push_str(&format!("- 步骤: {step} {}\n", STEPS[step].name));
+        out.push_str(&format!("- 错误: {error}\n"));
+        out.push_str(&format!("- 尝试次数: {}\n", self.pipeline.attempts[step]));
+        out.push_str(&format!("- 版本: podcast-studio v{}\n", update::CURRENT_VERSION));
+        out.push_str(&format!("- 系统: {} {}\n", std::env::consts::OS, std::env::consts::ARCH));
+        out.push_str(&format!("- 配置: {}\n", profile_label(self.settings.profile.as_deref())));
+
+        out.push_str("\n### 设置\n\n```\n");
+        for field in self.setting_groups.iter().flat_map(|(_, fields)| fields.iter()) {
+            let value = self.settings.get(field.key);
+            let shown = match field.field_type {
+                FieldType::Text { is_secret: true, .. } if !value.is_empty() => "(已设置)",
+                _ if value.is_empty() => "(未设置)",
+                _ => value,
+            };
+            out.push_str(&format!("{}={shown}\n", field.key));
+        }
+        out.push_str("```\n");
+
+        let redactor = Redactor::new(self.settings.secret_values(self.setting_groups));
+        let lines: Vec<&LogLine> = self.log_lines.iter().filter(|l| l.event.is_none()).collect();
+        let tail = &lines[lines.len().saturating_sub(LOG_TAIL)..];
+        out.push_str(&format!("\n### 最近 {} 行日志\n\n```\n", tail.len()));
+        for line in tail {
+            let mut line = (*line).clone();
+            redactor.redact(&mut line);
+            out.push_str(&line.text);
+            out.push('\n');
+        }
+        out.push_str("```\n");
+        out
     }
 
     // ── Settings page ─────────────────────────────────────────────
@@ -548,35 +3107,143 @@ impl PodcastApp {
     fn draw_settings_page(&mut self, ui: &mut egui::Ui) {
         ui.heading("设置");
         ui.add_space(4.0);
-        ui.label(
-            RichText::new(format!("配置文件: {}", self.settings.env_path.display()))
-                .color(Color32::from_rgb(156, 163, 175))
-                .size(12.0),
-        );
+        self.draw_display_settings(ui);
+        ui.add_space(4.0);
+        self.draw_profile_switcher(ui);
+        ui.add_space(4.0);
+        let secret_keys: Vec<&str> = self
+            .setting_groups
+            .iter()
+            .flat_map(|(_, fields)| fields.iter())
+            .filter_map(|f| matches!(f.field_type, FieldType::Text { is_secret: true, .. }).then_some(f.key))
+            .collect();
+        // On while every secret is shown; the per-field eye toggles still work alongside
+        let mut show_all = !secret_keys.is_empty()
+            && secret_keys.iter().all(|key| self.settings.visible_secrets.contains(*key));
+        if ui
+            .toggle_value(&mut show_all, "\u{1F441} 显示密钥")
+            .on_hover_text("一次显示或隐藏所有密钥，仅本次运行有效")
+            .changed()
+        {
+            if show_all {
+                self.settings.visible_secrets.extend(secret_keys.iter().map(|key| key.to_string()));
+            } else {
+                self.settings.visible_secrets.clear();
+            }
+        }
+        ui.horizontal(|ui| {
+            path_row(ui, "配置文件:", &self.settings.env_path);
+            if ui.small_button("打开 .env 文件").on_hover_text("手动编辑界面中没有的配置项").clicked() {
+                if !self.settings.env_path.exists() {
+                    // The OS opener can't open a missing file
+                    let _ = std::fs::write(&self.settings.env_path, "");
+                }
+                runner::open_in_editor(&self.settings.env_path);
+            }
+            if ui.small_button("重新加载").clicked() {
+                if self.settings.dirty {
+                    self.confirm_settings_reload = true;
+                } else {
+                    self.reload_settings();
+                }
+            }
+        });
+        if self.confirm_settings_reload {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    Color32::from_rgb(239, 68, 68),
+                    "有未保存的修改，重新加载将丢弃它们。",
+                );
+                if ui.button("丢弃并重新加载").clicked() {
+                    self.reload_settings();
+                }
+                if ui.button("取消").clicked() {
+                    self.confirm_settings_reload = false;
+                }
+            });
+        }
         ui.add_space(8.0);
 
+        if self.env_changed_externally {
+            egui::Frame::group(ui.style())
+                .stroke(egui::Stroke::new(1.0, Color32::from_rgb(234, 179, 8)))
+                .show(ui, |ui| {
+                    ui.colored_label(
+                        Color32::from_rgb(234, 179, 8),
+                        ".env 文件已在外部被修改。",
+                    );
+                    if self.settings.dirty {
+                        ui.colored_label(
+                            Color32::from_rgb(239, 68, 68),
+                            "你有未保存的修改：重新加载将丢弃它们，保存则会覆盖外部修改。",
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("重新加载").clicked() {
+                            self.reload_settings();
+                        }
+                        if ui.button("忽略").clicked() {
+                            self.settings.acknowledge_disk_change();
+                            self.env_changed_externally = false;
+                        }
+                    });
+                });
+            ui.add_space(8.0);
+        }
+
         ScrollArea::vertical().show(ui, |ui| {
-            for (group_name, fields) in SETTING_GROUPS {
+            for (group_name, fields) in self.setting_groups {
                 ui.add_space(8.0);
                 ui.label(RichText::new(*group_name).strong().size(14.0));
                 ui.separator();
 
                 egui::Grid::new(*group_name)
-                    .num_columns(3)
+                    .num_columns(4)
                     .spacing([8.0, 6.0])
                     .striped(true)
                     .show(ui, |ui| {
                         for field in *fields {
-                            ui.label(field.label);
+                            if self.settings_focus == Some(field.key) {
+                                let response = ui.label(
+                                    RichText::new(field.label).strong().color(Color32::from_rgb(234, 179, 8)),
+                                );
+                                if std::mem::take(&mut self.scroll_to_focus) {
+                                    response.scroll_to_me(Some(egui::Align::Center));
+                                }
+                            } else {
+                                ui.label(field.label);
+                            }
 
                             match &field.field_type {
                                 FieldType::Toggle => {
                                     let mut checked = self.settings.get_bool(field.key);
-                                    if ui.checkbox(&mut checked, "").changed() {
+                                    let locked = self.settings.is_last_tts_engine(field.key);
+                                    let response = ui
+                                        .add_enabled(!locked, egui::Checkbox::without_text(&mut checked))
+                                        .on_disabled_hover_text("至少需要启用一个 TTS 备用引擎");
+                                    if response.changed() {
                                         self.settings.set_bool(field.key, checked);
                                     }
                                     ui.label(""); // empty column
                                 }
+                                FieldType::Select { options } => {
+                                    let current = self.settings.get(field.key).to_string();
+                                    let shown = if current.is_empty() { "默认" } else { current.as_str() };
+                                    egui::ComboBox::from_id_salt(field.key)
+                                        .selected_text(shown)
+                                        .width(200.0)
+                                        .show_ui(ui, |ui| {
+                                            if ui.selectable_label(current.is_empty(), "默认").clicked() {
+                                                self.settings.set(field.key, String::new());
+                                            }
+                                            for option in *options {
+                                                if ui.selectable_label(current == *option, *option).clicked() {
+                                                    self.settings.set(field.key, option.to_string());
+                                                }
+                                            }
+                                        });
+                                    ui.label(""); // empty column
+                                }
                                 FieldType::Text { is_secret, placeholder } => {
                                     let mut val = self.settings.get(field.key).to_string();
                                     let is_visible = !is_secret
@@ -616,9 +3283,25 @@ impl PodcastApp {
                                 }
                             }
 
+                            if self.settings.is_changed(field.key) {
+                                ui.horizontal(|ui| {
+                                    ui.colored_label(Color32::from_rgb(234, 179, 8), "●")
+                                        .on_hover_text("已修改，尚未保存");
+                                    if ui.small_button("撤销").clicked() {
+                                        self.settings.revert(field.key);
+                                    }
+                                });
+                            } else {
+                                ui.label("");
+                            }
+
                             ui.end_row();
                         }
                     });
+
+                for warning in self.settings.group_warnings(fields) {
+                    ui.colored_label(Color32::from_rgb(234, 179, 8), format!("⚠ {warning}"));
+                }
             }
 
             ui.add_space(16.0);
@@ -628,65 +3311,637 @@ impl PodcastApp {
                 ui.add_enabled_ui(save_enabled, |ui| {
                     if ui.button("保存").clicked() {
                         match self.settings.save() {
-                            Ok(()) => self.settings_status = "已保存".to_string(),
-                            Err(e) => self.settings_status = e,
+                            Ok(()) => {
+                                self.settings_status.clear();
+                                self.toasts.success("设置已保存");
+                                self.env_changed_externally = false;
+                            }
+                            Err(e) => {
+                                self.toasts.error("设置保存失败");
+                                self.settings_status = e;
+                            }
+                        }
+                    }
+                });
+
+                // Only errors stay on screen; successes are toasts
+                if !self.settings_status.is_empty() {
+                    ui.colored_label(Color32::from_rgb(239, 68, 68), &self.settings_status);
+                }
+
+                if self.settings.dirty {
+                    ui.colored_label(Color32::from_rgb(234, 179, 8), "(未保存)");
+                }
+            });
+        });
+    }
+
+    /// UI scale and text size; app preferences, so stored with the UI state, not in .env.
+    fn draw_display_settings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("界面缩放");
+            let scale = ui.add(
+                egui::Slider::new(&mut self.ui_scale, MIN_UI_SCALE..=MAX_UI_SCALE)
+                    .step_by(0.1)
+                    .suffix("x"),
+            );
+            // Apply once the drag ends, so the slider doesn't move under the pointer
+            if scale.drag_stopped() || (scale.changed() && !scale.dragged()) {
+                self.apply_ui_scale(ui.ctx());
+            }
+            ui.add_space(16.0);
+            ui.label("字号");
+            let size = ui.add(
+                egui::DragValue::new(&mut self.ui_font_size)
+                    .range(MIN_UI_FONT_SIZE..=MAX_UI_FONT_SIZE)
+                    .speed(0.5),
+            );
+            if size.changed() {
+                apply_font_size(ui.ctx(), self.ui_font_size);
+                self.save_recent_paths();
+            }
+            if ui.small_button("恢复默认").clicked() {
+                self.ui_scale = 1.0;
+                self.ui_font_size = DEFAULT_UI_FONT_SIZE;
+                self.apply_ui_scale(ui.ctx());
+                apply_font_size(ui.ctx(), self.ui_font_size);
+            }
+            ui.add_space(16.0);
+            if ui
+                .checkbox(&mut self.compact_mode, "紧凑模式")
+                .on_hover_text("输出日志默认折叠为最后一行，步骤失败时自动展开")
+                .changed()
+            {
+                self.save_recent_paths();
+            }
+            if ui
+                .checkbox(&mut self.log_show_secrets, "日志显示密钥")
+                .on_hover_text("仅供调试：不再把日志中的 API Key 等密钥替换为 ****，从下次运行起生效")
+                .changed()
+            {
+                self.save_recent_paths();
+            }
+        });
+    }
+
+    fn apply_ui_scale(&mut self, ctx: &egui::Context) {
+        ctx.set_zoom_factor(self.ui_scale);
+        self.ui_scale_applied = self.ui_scale;
+        self.save_recent_paths();
+    }
+
+    fn draw_profile_switcher(&mut self, ui: &mut egui::Ui) {
+        let profiles = settings::available_profiles(&self.project_root);
+        let current = self.settings.profile.clone();
+        let mut switch_to = None;
+
+        ui.horizontal(|ui| {
+            ui.label("当前配置:");
+            // Switching discards in-memory edits, so require a save first
+            ui.add_enabled_ui(!self.settings.dirty, |ui| {
+                egui::ComboBox::from_id_salt("profile_switcher")
+                    .selected_text(profile_label(current.as_deref()))
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(current.is_none(), profile_label(None)).clicked() {
+                            switch_to = Some(None);
+                        }
+                        for name in &profiles {
+                            let selected = current.as_deref() == Some(name.as_str());
+                            if ui.selectable_label(selected, name).clicked() {
+                                switch_to = Some(Some(name.clone()));
+                            }
+                        }
+                    });
+            })
+            .response
+            .on_disabled_hover_text("请先保存当前修改");
+
+            ui.add_space(12.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_profile_name)
+                    .hint_text("新配置名, 如 prod")
+                    .desired_width(120.0),
+            );
+            if ui.button("新建").clicked() {
+                let name = self.new_profile_name.trim().to_string();
+                match settings::create_profile(&self.project_root, &name, &self.settings.env_path) {
+                    Ok(()) => {
+                        self.new_profile_name.clear();
+                        if !self.settings.dirty {
+                            switch_to = Some(Some(name));
+                        }
+                    }
+                    Err(e) => self.settings_status = e,
+                }
+            }
+        });
+
+        if let Some(profile) = switch_to {
+            if profile != current {
+                self.switch_profile(profile);
+            }
+        }
+    }
+
+    fn draw_publish_preview(&mut self, ui: &mut egui::Ui) {
+        let Some(result) = &self.publish_preview else {
+            return;
+        };
+        ui.add_space(8.0);
+        let preview = match result {
+            Ok(preview) => preview,
+            Err(e) => {
+                ui.colored_label(Color32::from_rgb(239, 68, 68), e);
+                return;
+            }
+        };
+        let mut close = false;
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("发布预览 (未上传)").strong());
+                if ui.small_button("关闭").clicked() {
+                    close = true;
+                }
+            });
+            let or_none = |s: &str| if s.is_empty() { "(无)".to_string() } else { s.to_string() };
+            egui::Grid::new("publish_preview").num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+                ui.label("标题");
+                ui.label(&preview.title);
+                ui.end_row();
+                ui.label("作者");
+                ui.label(&preview.author);
+                ui.end_row();
+                ui.label("摘要");
+                ui.label(&preview.digest);
+                ui.end_row();
+                ui.label("封面");
+                if preview.cover_path.is_empty() {
+                    ui.label("(无，将使用默认封面)");
+                } else {
+                    let cover = Path::new(&preview.cover_path);
+                    ui.horizontal(|ui| {
+                        ui.label(cover.file_name().map_or_else(
+                            || preview.cover_path.clone(),
+                            |n| n.to_string_lossy().into_owned(),
+                        ));
+                        if ui.add_enabled(cover.exists(), egui::Button::new("打开").small()).clicked() {
+                            runner::open_in_editor(cover);
                         }
-                    }
-                });
-
-                if !self.settings_status.is_empty() {
-                    let color = if self.settings_status.starts_with("已") {
-                        Color32::from_rgb(34, 197, 94)
-                    } else {
-                        Color32::from_rgb(239, 68, 68)
-                    };
-                    ui.colored_label(color, &self.settings_status);
+                    });
                 }
-
-                if self.settings.dirty {
-                    ui.colored_label(Color32::from_rgb(234, 179, 8), "(未保存)");
+                ui.end_row();
+                ui.label("音频 CDN");
+                if preview.mp3_url.is_empty() {
+                    ui.colored_label(Color32::from_rgb(234, 179, 8), "(无，正文中不会包含音频链接)");
+                } else {
+                    text_row(ui, "", &preview.mp3_url);
+                }
+                ui.end_row();
+                ui.label("阅读原文");
+                if preview.source_url.is_empty() {
+                    ui.label(or_none(&preview.source_url));
+                } else {
+                    text_row(ui, "", &preview.source_url);
                 }
+                ui.end_row();
             });
+            if !preview.html_path.is_empty() {
+                let html = Path::new(&preview.html_path);
+                ui.horizontal(|ui| {
+                    ui.label(format!("正文 HTML: {} 字符", preview.html_chars));
+                    if ui.add_enabled(html.exists(), egui::Button::new("在浏览器中查看")).clicked() {
+                        runner::open_in_editor(html);
+                    }
+                });
+            }
         });
+        if close {
+            self.publish_preview = None;
+        }
     }
 
     // ── Log panel (shared by steps 1, 3, 4) ─────────────────────
 
-    fn draw_log_panel(&self, ui: &mut egui::Ui) {
+    fn draw_log_panel(&mut self, ui: &mut egui::Ui) {
         if self.log_lines.is_empty() {
             return;
         }
 
         ui.add_space(8.0);
         ui.separator();
+        if !self.compact_mode {
+            ui.horizontal(|ui| self.draw_log_controls(ui));
+            self.draw_log_body(ui);
+            return;
+        }
+
+        // Compact mode: folded to the latest line, but opened once when the step fails
+        let id = ui.make_persistent_id("log_panel");
+        let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false);
+        let failed = matches!(self.pipeline.steps[self.pipeline.current_step], StepStatus::Failed(_));
+        if failed && !self.log_opened_on_failure {
+            state.set_open(true);
+            self.log_opened_on_failure = true;
+        }
+        let open = state.is_open();
+        state
+            .show_header(ui, |ui| {
+                if open {
+                    self.draw_log_controls(ui);
+                } else if let Some(line) = self.log_lines.iter().rev().find(|l| l.event.is_none()) {
+                    ui.label(RichText::new("输出日志").strong());
+                    let text = RichText::new(&line.text).monospace().color(log_line_color(line));
+                    ui.add(egui::Label::new(text).truncate());
+                }
+            })
+            .body(|ui| self.draw_log_body(ui));
+    }
+
+    /// Title and display options shown above the log.
+    fn draw_log_controls(&mut self, ui: &mut egui::Ui) {
         ui.label(RichText::new("输出日志").strong());
+        if let Some(phase) = self.log_phases.last().filter(|_| self.run_handle.is_some()) {
+            ui.label(RichText::new(format!("· {}", phase.name)).color(Color32::from_rgb(59, 130, 246)));
+        }
+        ui.toggle_value(&mut self.collapse_log_duplicates, "合并重复行");
+        if ui
+            .toggle_value(&mut self.log_follow, "跟随最新")
+            .on_hover_text("关闭后日志停在当前位置，方便阅读")
+            .changed()
+            && self.log_follow
+        {
+            self.log_scroll_to_end = true;
+        }
+        let mut changed = ui.toggle_value(&mut self.log_wrap, "自动换行").changed();
+        ui.label("字号");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut self.log_font_size)
+                    .range(MIN_LOG_FONT_SIZE..=MAX_LOG_FONT_SIZE)
+                    .speed(0.5),
+            )
+            .changed();
+        ui.label("级别");
+        egui::ComboBox::from_id_salt("log_min_level")
+            .selected_text(self.log_min_level.map_or("全部", LogLevel::label))
+            .width(90.0)
+            .show_ui(ui, |ui| {
+                changed |= ui.selectable_value(&mut self.log_min_level, None, "全部").changed();
+                for level in LogLevel::ALL {
+                    changed |= ui
+                        .selectable_value(&mut self.log_min_level, Some(level), level.label())
+                        .on_hover_text("隐藏低于此级别的日志")
+                        .changed();
+                }
+            });
+        if changed {
+            self.save_recent_paths();
+        }
+        if !self.log_tracebacks.is_empty() && ui.button("跳转到错误").clicked() {
+            self.scroll_to_traceback = true;
+        }
+    }
+
+    /// The log lines themselves, grouped by phase.
+    fn draw_log_body(&mut self, ui: &mut egui::Ui) {
+        let style = LogStyle {
+            collapse: self.collapse_log_duplicates,
+            wrap: self.log_wrap,
+            font_size: self.log_font_size,
+            min_level: self.log_min_level,
+        };
+
+        // When a new phase starts, fold the earlier ones so the latest stays in view
+        let phase_count = self.log_phases.len();
+        if phase_count > self.log_phases_seen {
+            for i in 0..phase_count - 1 {
+                let id = ui.make_persistent_id(("log_phase", i));
+                let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ui.ctx(),
+                    id,
+                    false,
+                );
+                state.set_open(false);
+                state.store(ui.ctx());
+            }
+            self.log_phases_seen = phase_count;
+        }
 
-        ScrollArea::vertical()
-            .max_height(ui.available_height() - 20.0)
-            .stick_to_bottom(true)
+        let scroll_target = std::mem::take(&mut self.scroll_to_traceback)
+            .then(|| self.log_tracebacks.first().map(|tb| tb.start))
+            .flatten();
+        if let Some(target) = scroll_target {
+            // The traceback may sit in a folded phase
+            if let Some(i) = self.log_phases.iter().rposition(|p| p.start <= target) {
+                let id = ui.make_persistent_id(("log_phase", i));
+                let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ui.ctx(),
+                    id,
+                    false,
+                );
+                state.set_open(true);
+                state.store(ui.ctx());
+            }
+        }
+        let tracebacks = self.log_tracebacks.as_slice();
+
+        // Unwrapped lines need a horizontal scrollbar to stay readable
+        let mut scroll = if style.wrap { ScrollArea::vertical() } else { ScrollArea::both() };
+        if std::mem::take(&mut self.log_scroll_to_end) {
+            scroll = scroll.vertical_scroll_offset(f32::MAX);
+        }
+        // One scroll position per step, kept while other steps or the settings page are shown
+        scroll
+            .id_salt(("log_scroll", self.log_step))
+            .stick_to_bottom(self.log_follow)
             .show(ui, |ui| {
-                for line in &self.log_lines {
-                    let color = if line.is_stderr {
-                        Color32::from_rgb(234, 179, 8) // yellow for stderr
-                    } else {
-                        Color32::from_rgb(209, 213, 219) // light gray
-                    };
-                    ui.monospace(RichText::new(&line.text).color(color).size(12.0));
+                let first = self.log_phases.first().map_or(self.log_lines.len(), |p| p.start);
+                draw_log_section(ui, &self.log_lines[..first], 0, style, tracebacks, scroll_target);
+
+                for (i, phase) in self.log_phases.iter().enumerate() {
+                    let end = self.log_phases.get(i + 1).map_or(self.log_lines.len(), |p| p.start);
+                    // Skip the marker line itself; the header shows its name
+                    let lines = &self.log_lines[phase.start + 1..end];
+                    let id = ui.make_persistent_id(("log_phase", i));
+                    egui::collapsing_header::CollapsingState::load_with_default_open(
+                        ui.ctx(),
+                        id,
+                        i + 1 == phase_count,
+                    )
+                    .show_header(ui, |ui| {
+                        ui.label(RichText::new(&phase.name).strong());
+                        ui.label(
+                            RichText::new(format!("{} 行", lines.len()))
+                                .color(Color32::from_rgb(156, 163, 175))
+                                .size(11.0),
+                        );
+                    })
+                    .body(|ui| draw_log_section(ui, lines, phase.start + 1, style, tracebacks, scroll_target));
                 }
             });
     }
 }
 
+impl PodcastApp {
+    /// Startup prompt for a step that was still running when the app last exited.
+    fn draw_resume_prompt(&mut self, ctx: &egui::Context) {
+        let Some(run) = &self.interrupted_run else {
+            return;
+        };
+        let step = run.step.min(STEPS.len() - 1);
+        let mut choice = None;
+        egui::Window::new("恢复任务")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("上次任务未正常结束，是否从第 {step} 步继续？"));
+                let target = run.work_dir.as_ref().or(run.pdf_path.as_ref());
+                let detail = match target {
+                    Some(path) => format!("{}: {}", STEPS[step].name, path.display()),
+                    None => STEPS[step].name.to_string(),
+                };
+                ui.label(RichText::new(detail).small().color(Color32::from_rgb(156, 163, 175)));
+                if step == 4 {
+                    ui.label(
+                        RichText::new("发布不会自动重新运行，请确认草稿状态后再手动发布")
+                            .small()
+                            .color(Color32::from_rgb(234, 179, 8)),
+                    );
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("继续").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("放弃").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+        if let Some(resume) = choice {
+            let run = self.interrupted_run.take();
+            self.save_recent_paths();
+            if let (true, Some(run)) = (resume, run) {
+                self.resume_interrupted_run(run);
+            }
+        }
+    }
+
+    /// Wait for the killed job to stop, then close; gives up after `EXIT_STOP_TIMEOUT`.
+    fn draw_exit_stopping(&mut self, ctx: &egui::Context, since: Instant) {
+        if self.run_handle.is_none() || since.elapsed() >= EXIT_STOP_TIMEOUT {
+            self.exit_stopping_since = None;
+            self.exit_confirmed = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+        egui::Window::new("正在退出")
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("正在停止任务...");
+                });
+            });
+        ctx.request_repaint_after(Duration::from_millis(100));
+    }
+
+    fn draw_exit_confirm(&mut self, ctx: &egui::Context) {
+        egui::Window::new("确认退出")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("任务运行中，确定退出？正在运行的任务将被终止。");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("终止并退出").clicked() {
+                        self.confirm_exit = false;
+                        if let Some(handle) = &self.run_handle {
+                            handle.kill();
+                            self.exit_stopping_since = Some(Instant::now());
+                        } else {
+                            self.exit_confirmed = true;
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                    }
+                    if ui.button("取消").clicked() {
+                        self.confirm_exit = false;
+                    }
+                });
+            });
+    }
+}
+
+impl PodcastApp {
+    /// Last check before `publish-podcast`: which account, which episode, which command.
+    fn draw_publish_confirm(&mut self, ctx: &egui::Context) {
+        let Some(dir) = self.pipeline.work_dir.clone() else {
+            self.confirm_publish = false;
+            return;
+        };
+        // The draft title as edited on the publish page, which is what gets sent
+        let title = match &self.draft_fields {
+            Some(fields) if fields.work_dir == dir => fields.title.trim().to_string(),
+            _ => DraftFields::load(&dir).title,
+        };
+        let title = if title.is_empty() { "(无标题)".to_string() } else { title };
+        let app_id = self.settings.get("WECHAT_APP_ID");
+        let entrypoint = runner::Entrypoint::parse(self.settings.get("PYTHON_ENTRYPOINT"));
+        let dir_display = dir.display().to_string();
+        let series_args = self.series_args();
+        let mut args = vec!["publish-podcast", "--podcast-dir", &dir_display];
+        if self.publish_upload_audio {
+            args.push("--upload-audio");
+        }
+        args.extend(series_args.iter().map(String::as_str));
+        let command = entrypoint.command_line(&self.project_root, &args);
+        let series = self.series_id.as_deref().and_then(|id| self.series.get(id));
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("确认发布")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(if self.publish_upload_audio {
+                    "将上传音频和封面，并在以下公众号创建草稿："
+                } else {
+                    "将沿用已上传的音频，在以下公众号创建草稿："
+                });
+                ui.add_space(4.0);
+                egui::Grid::new("publish_confirm").num_columns(2).show(ui, |ui| {
+                    ui.label("配置:");
+                    ui.label(profile_label(self.settings.profile.as_deref()));
+                    ui.end_row();
+                    ui.label("App ID:");
+                    ui.monospace(if app_id.is_empty() { "(未设置)" } else { app_id });
+                    ui.end_row();
+                    ui.label("标题:");
+                    ui.label(&title);
+                    ui.end_row();
+                    if let Some(series) = series {
+                        ui.label("系列:");
+                        ui.label(format!("{} 第 {} 期 (标题按系列模板生成)", series.name, self.series_episode));
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(4.0);
+                text_row(ui, "命令:", &command);
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("确认发布").clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            self.confirm_publish = false;
+            self.start_publish();
+        } else if cancelled {
+            self.confirm_publish = false;
+            if self.run_all {
+                self.stop_run_all();
+            }
+        }
+    }
+}
+
 impl eframe::App for PodcastApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Never leave an orphaned Python process writing into work_dir
+        if let Some(handle) = self.run_handle.take() {
+            if self.exit_confirmed {
+                // Already killed and waited for when quitting; don't hang on it again
+                handle.kill();
+            } else {
+                handle.shutdown();
+            }
+        }
+        if self.running_step.take().is_some() {
+            self.save_recent_paths();
+        }
+        self.flush_recent_paths(true);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_font_loader();
+
+        // Keep the setting in sync with egui's own Ctrl +/- zoom
+        if ctx.zoom_factor() != self.ui_scale_applied {
+            self.ui_scale = ctx.zoom_factor().clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+            self.apply_ui_scale(ctx);
+        }
+
         // Poll subprocess
         self.poll_subprocess();
+        self.drive_run_all();
+        self.track_running_step();
+        self.autosave_script(ctx);
 
         // Request repaint while subprocess is running
         if self.run_handle.is_some() {
             ctx.request_repaint();
         }
 
+        // Closing while a job runs: ask first, the child is killed in on_exit
+        if ctx.input(|i| i.viewport().close_requested())
+            && self.run_handle.is_some()
+            && !self.exit_confirmed
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.confirm_exit = self.exit_stopping_since.is_none();
+        }
+        if self.confirm_exit {
+            self.draw_exit_confirm(ctx);
+        }
+        if let Some(since) = self.exit_stopping_since {
+            self.draw_exit_stopping(ctx, since);
+        }
+        if self.interrupted_run.is_some() {
+            self.draw_resume_prompt(ctx);
+        }
+        if self.confirm_publish {
+            self.draw_publish_confirm(ctx);
+        }
+        if self.confirm_regenerate {
+            self.draw_regenerate_confirm(ctx);
+        }
+
+        // Persist picker/UI state in batches
+        self.flush_recent_paths(false);
+        if self.recent_dirty {
+            ctx.request_repaint_after(RECENT_SAVE_INTERVAL);
+        }
+
+        // Poll .env for external edits about once a second
+        if self.env_checked_at.elapsed() >= Duration::from_secs(1) {
+            self.env_checked_at = Instant::now();
+            if self.settings.changed_on_disk() {
+                self.env_changed_externally = true;
+            }
+            if self.pipeline.current_step >= 2 && self.run_handle.is_none() {
+                self.forget_missing_work_dir();
+            }
+        }
+        ctx.request_repaint_after(Duration::from_secs(1));
+
+        // While a job runs, navigation is locked so nothing changes under it;
+        // "全部中止" stays available. Widgets drawn later may still set their own cursor.
+        let busy = self.run_handle.is_some();
+        if busy {
+            ctx.set_cursor_icon(egui::CursorIcon::Progress);
+        }
+
         // Bottom bar: page navigation
         egui::TopBottomPanel::bottom("nav_bar").show(ctx, |ui| {
             ui.add_space(4.0);
@@ -694,46 +3949,131 @@ impl eframe::App for PodcastApp {
                 let pipeline_selected = self.page == Page::Pipeline;
                 let settings_selected = self.page == Page::Settings;
 
-                if ui.selectable_label(pipeline_selected, "制作").clicked() {
+                let locked_hint = "任务运行中，请先等待完成或中止";
+                if ui
+                    .add_enabled(!busy, egui::SelectableLabel::new(pipeline_selected, "制作"))
+                    .on_disabled_hover_text(locked_hint)
+                    .clicked()
+                {
                     self.page = Page::Pipeline;
+                    self.settings_focus = None;
                 }
-                if ui.selectable_label(settings_selected, "设置").clicked() {
+                if ui
+                    .add_enabled(!busy, egui::SelectableLabel::new(settings_selected, "设置"))
+                    .on_disabled_hover_text(locked_hint)
+                    .clicked()
+                {
                     self.page = Page::Settings;
                 }
+
+                ui.separator();
+                let profile = self.settings.profile.as_deref();
+                let profile_text = RichText::new(format!("配置: {}", profile_label(profile)));
+                if profile.is_some() {
+                    ui.label(profile_text.strong().color(Color32::from_rgb(234, 88, 12)));
+                } else {
+                    ui.label(profile_text);
+                }
+
+                ui.separator();
+                let abort = if busy {
+                    egui::Button::new(RichText::new("全部中止").color(Color32::WHITE))
+                        .fill(Color32::from_rgb(239, 68, 68))
+                } else {
+                    egui::Button::new("全部中止")
+                };
+                if ui
+                    .add_enabled(busy, abort)
+                    .on_hover_text("终止所有正在运行的任务")
+                    .clicked()
+                {
+                    self.abort_all();
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    self.draw_version_info(ui);
+                    ui.separator();
+                    let done = self.pipeline.done_count();
+                    let total = self.pipeline.steps.len();
+                    ui.label(format!("{done}/{total} 完成"));
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / total as f32)
+                            .desired_width(120.0)
+                            .desired_height(8.0),
+                    );
+                    ui.label("整体进度");
+                });
             });
             ui.add_space(2.0);
         });
 
         match self.page {
             Page::Pipeline => {
-                // Left panel: timeline
-                egui::SidePanel::left("timeline_panel")
-                    .min_width(180.0)
-                    .max_width(220.0)
-                    .resizable(false)
-                    .show(ctx, |ui| {
-                        ui.add_space(8.0);
-
-                        if let Some(clicked) = timeline::draw_timeline(
-                            ui,
-                            &self.pipeline.steps,
-                            self.pipeline.current_step,
-                        ) {
-                            self.jump_to_step(clicked);
-                        }
-
-                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
-                            ui.add_space(8.0);
-                            if ui.small_button("重置").clicked() {
-                                self.pipeline.reset();
-                                self.log_lines.clear();
-                                self.script_content.clear();
-                                self.script_dirty = false;
-                                self.run_handle = None;
-                            }
-                            ui.add_space(4.0);
+                // Left panel: timeline, reduced to its circles in narrow windows
+                let compact = ctx.screen_rect().width() < COMPACT_LAYOUT_WIDTH;
+                // Grows with the font size setting, like the timeline drawn in it
+                let scale = timeline::font_scale(&ctx.style());
+                let panel = egui::SidePanel::left("timeline_panel").resizable(false);
+                let panel = if compact {
+                    panel.exact_width(COMPACT_TIMELINE_WIDTH * scale)
+                } else {
+                    panel.min_width(180.0 * scale).max_width(220.0 * scale)
+                };
+                panel.show(ctx, |ui| {
+                    // Laid out first so the timeline gets exactly the height left above it
+                    egui::TopBottomPanel::bottom("timeline_actions")
+                        .frame(egui::Frame::NONE)
+                        .show_separator_line(false)
+                        .resizable(false)
+                        .show_inside(ui, |ui| {
+                            ui.vertical_centered(|ui| {
+                                ui.add_space(4.0);
+                                let failed = self.failed_steps().len();
+                                if failed > 0 && !busy && !self.run_all {
+                                    let label = if compact { "↻".to_string() } else { format!("重试失败项 ({failed})") };
+                                    if ui
+                                        .button(label)
+                                        .on_hover_text("把失败的步骤重新排队，按顺序再运行一次")
+                                        .clicked()
+                                    {
+                                        let queued = self.retry_failed();
+                                        self.toasts.success(format!("已重新排队 {queued} 个失败步骤"));
+                                    }
+                                    ui.add_space(4.0);
+                                }
+                                if ui
+                                    .add_enabled(!busy, egui::Button::new("重置").small())
+                                    .on_disabled_hover_text("任务运行中，请先中止")
+                                    .clicked()
+                                {
+                                    self.pipeline.reset();
+                                    self.work_dir_missing = None;
+                                    self.clear_log();
+                                    self.command_history.clear();
+                                    self.script_content.clear();
+                                    self.script_history.reset("");
+                                    self.script_dirty = false;
+                                    if let Some(handle) = self.run_handle.take() {
+                                        handle.kill();
+                                    }
+                                    self.publish_dry_run = false;
+                                    self.publish_preview = None;
+                                    self.run_all = false;
+                                }
+                                ui.add_space(8.0);
+                            });
                         });
-                    });
+
+                    ui.add_space(8.0);
+                    let clicked = ui
+                        .add_enabled_ui(!busy, |ui| {
+                            timeline::draw_timeline(ui, &self.pipeline.steps, self.pipeline.current_step, compact)
+                        })
+                        .inner;
+                    if let Some(clicked) = clicked {
+                        self.jump_to_step(clicked);
+                    }
+                });
 
                 // Central panel: step content
                 egui::CentralPanel::default().show(ctx, |ui| {
@@ -746,9 +4086,423 @@ impl eframe::App for PodcastApp {
                 });
             }
         }
+
+        // Every "复制" button goes through the clipboard command, so confirm them all here
+        let copied = ctx.output(|o| o.commands.iter().any(|c| matches!(c, egui::OutputCommand::CopyText(_))));
+        if copied {
+            self.toasts.success("已复制");
+        }
+        self.toasts.show(ctx);
+    }
+}
+
+/// Render a line diff, eliding long runs of unchanged lines.
+fn draw_diff<T: AsRef<str>>(ui: &mut egui::Ui, id_salt: &str, lines: &[DiffLine<T>]) {
+    const CONTEXT: usize = 2;
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| !matches!(l, DiffLine::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+    if changed.is_empty() {
+        ui.label("没有差异");
+        return;
+    }
+    let near_change = |i: usize| changed.iter().any(|&c| c.abs_diff(i) <= CONTEXT);
+
+    ScrollArea::vertical()
+        .id_salt(id_salt)
+        .max_height(240.0)
+        .show(ui, |ui| {
+            let mut elided = false;
+            for (i, line) in lines.iter().enumerate() {
+                let (prefix, text, color) = match line {
+                    DiffLine::Same(_) if !near_change(i) => {
+                        if !elided {
+                            ui.monospace(RichText::new("  ⋯").color(Color32::from_rgb(156, 163, 175)).size(12.0));
+                            elided = true;
+                        }
+                        continue;
+                    }
+                    DiffLine::Same(t) => (" ", t, Color32::from_rgb(156, 163, 175)),
+                    DiffLine::Removed(t) => ("−", t, Color32::from_rgb(239, 68, 68)),
+                    DiffLine::Added(t) => ("+", t, Color32::from_rgb(34, 197, 94)),
+                };
+                elided = false;
+                ui.monospace(RichText::new(format!("{prefix} {}", text.as_ref())).color(color).size(12.0));
+            }
+        });
+}
+
+/// Display name for a settings profile.
+fn profile_label(profile: Option<&str>) -> &str {
+    profile.unwrap_or("默认 (.env)")
+}
+
+/// "本次消耗 N tokens" for the script run recorded in `generation.json`.
+fn draw_token_usage(ui: &mut egui::Ui, params: &GenerationParams, price_per_1k: Option<f64>) {
+    let total = params.total_tokens();
+    if total == 0 {
+        return;
+    }
+    let mut text = format!(
+        "本次消耗 {total} tokens (输入 {} / 输出 {})",
+        params.prompt_tokens, params.completion_tokens
+    );
+    if let Some(price) = price_per_1k {
+        text.push_str(&format!("，估算费用 {:.4}", params.estimated_cost(price)));
+    }
+    ui.label(RichText::new(text).color(Color32::from_rgb(156, 163, 175)).size(12.0));
+}
+
+/// `1234567` as `1,234,567`.
+fn format_thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Label, monospace path and a "复制" button on one row.
+fn path_row(ui: &mut egui::Ui, label: &str, path: &Path) {
+    text_row(ui, label, &path.display().to_string());
+}
+
+/// Like [`path_row`] for URLs and other copyable strings; an empty label is omitted.
+fn text_row(ui: &mut egui::Ui, label: &str, text: &str) {
+    ui.horizontal(|ui| {
+        if !label.is_empty() {
+            ui.label(label);
+        }
+        ui.monospace(text);
+        if ui.small_button("复制").clicked() {
+            ui.ctx().copy_text(text.to_string());
+        }
+    });
+}
+
+/// Read `metadata.json` written by the pipeline into a work dir.
+fn read_metadata(work_dir: &Path) -> Option<serde_json::Value> {
+    let content = std::fs::read_to_string(work_dir.join("metadata.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Read-only "生成参数" line for episodes that recorded their generation settings.
+fn draw_generation_params(ui: &mut egui::Ui, params: &GenerationParams) {
+    ui.label(
+        RichText::new(format!("生成参数: {}", params.summary()))
+            .color(Color32::from_rgb(156, 163, 175))
+            .size(12.0),
+    );
+}
+
+/// Width of the outline column beside the structured script editor.
+const OUTLINE_WIDTH: f32 = 160.0;
+
+/// Below this window width the timeline shrinks to an icons-only strip.
+const COMPACT_LAYOUT_WIDTH: f32 = 900.0;
+const COMPACT_TIMELINE_WIDTH: f32 = 56.0;
+
+const MIN_UI_SCALE: f32 = 0.8;
+const MAX_UI_SCALE: f32 = 2.0;
+/// egui's default body text size.
+const DEFAULT_UI_FONT_SIZE: f32 = 12.5;
+const MIN_UI_FONT_SIZE: f32 = 10.0;
+const MAX_UI_FONT_SIZE: f32 = 24.0;
+
+/// Scale every text style from egui's defaults so body text is `body_size`.
+fn apply_font_size(ctx: &egui::Context, body_size: f32) {
+    let factor = body_size / DEFAULT_UI_FONT_SIZE;
+    let defaults = egui::Style::default().text_styles;
+    ctx.style_mut(|style| {
+        for (text_style, font) in &mut style.text_styles {
+            if let Some(default) = defaults.get(text_style) {
+                font.size = default.size * factor;
+            }
+        }
+    });
+}
+
+/// Log lines taken from a running step per frame; a chatty step catches up
+/// over the following frames instead of stalling one.
+const LOG_LINES_PER_FRAME: usize = 1000;
+
+const DEFAULT_LOG_FONT_SIZE: f32 = 12.0;
+const MIN_LOG_FONT_SIZE: f32 = 9.0;
+const MAX_LOG_FONT_SIZE: f32 = 20.0;
+
+/// How log lines are rendered.
+#[derive(Clone, Copy)]
+struct LogStyle {
+    collapse: bool,
+    wrap: bool,
+    font_size: f32,
+    min_level: Option<LogLevel>,
+}
+
+/// Render `lines`, which start at index `offset` of the log, boxing the parts
+/// that belong to a traceback and scrolling to the one starting at `scroll_to`.
+fn draw_log_section(
+    ui: &mut egui::Ui,
+    lines: &[LogLine],
+    offset: usize,
+    style: LogStyle,
+    tracebacks: &[Range<usize>],
+    scroll_to: Option<usize>,
+) {
+    let end = offset + lines.len();
+    let mut pos = offset;
+    for tb in tracebacks.iter().filter(|tb| tb.start < end && tb.end > offset) {
+        let (start, stop) = (tb.start.max(pos), tb.end.min(end));
+        draw_log_lines(ui, &lines[pos - offset..start - offset], style);
+        let red = Color32::from_rgb(239, 68, 68);
+        let response = egui::Frame::group(ui.style())
+            .stroke(egui::Stroke::new(1.0, red))
+            .fill(red.gamma_multiply(0.08))
+            .show(ui, |ui| draw_log_lines(ui, &lines[start - offset..stop - offset], style))
+            .response;
+        if scroll_to == Some(tb.start) {
+            response.scroll_to_me(Some(egui::Align::TOP));
+        }
+        pos = stop;
+    }
+    draw_log_lines(ui, &lines[pos - offset..], style);
+}
+
+/// Render log lines, optionally collapsing consecutive duplicates.
+fn draw_log_lines(ui: &mut egui::Ui, lines: &[LogLine], style: LogStyle) {
+    let wrap_mode = if style.wrap { egui::TextWrapMode::Wrap } else { egui::TextWrapMode::Extend };
+    // Only rendering filters and collapses; `log_lines` keeps every raw line
+    let visible: Vec<&LogLine> = lines
+        .iter()
+        // Event lines drive the UI, they are not log text
+        .filter(|line| line.event.is_none())
+        .filter(|line| match (style.min_level, line.level) {
+            (Some(min), Some(level)) => level >= min,
+            _ => true,
+        })
+        .collect();
+    for group in visible.chunk_by(|a, b| style.collapse && a.text == b.text) {
+        let line = group[0];
+        let text = RichText::new(&line.text)
+            .monospace()
+            .color(log_line_color(line))
+            .size(style.font_size);
+        let label = egui::Label::new(text).wrap_mode(wrap_mode);
+        if group.len() > 1 {
+            ui.horizontal(|ui| {
+                ui.add(label);
+                ui.label(
+                    RichText::new(format!("×{}", group.len()))
+                        .color(Color32::from_rgb(156, 163, 175))
+                        .size(style.font_size - 1.0),
+                );
+            });
+        } else {
+            ui.add(label);
+        }
+    }
+}
+
+/// Color for a log line: parsed level first, falling back to the stream.
+fn log_line_color(line: &LogLine) -> Color32 {
+    match line.level {
+        Some(LogLevel::Debug) => Color32::from_rgb(156, 163, 175), // gray
+        Some(LogLevel::Info) => Color32::from_rgb(229, 231, 235), // white
+        Some(LogLevel::Warning) => Color32::from_rgb(234, 179, 8), // yellow
+        Some(LogLevel::Error | LogLevel::Critical) => Color32::from_rgb(239, 68, 68), // red
+        None if line.is_stderr => Color32::from_rgb(234, 179, 8), // yellow for stderr
+        None => Color32::from_rgb(209, 213, 219), // light gray
+    }
+}
+
+/// Show size and duration of a generated audio file, warning if it looks truncated.
+fn draw_audio_info(ui: &mut egui::Ui, info: Option<&audio::AudioInfo>) {
+    let Some(info) = info else {
+        ui.colored_label(Color32::from_rgb(239, 68, 68), "音频文件不存在");
+        return;
+    };
+    let duration = info
+        .duration_secs
+        .map(audio::format_duration)
+        .unwrap_or_else(|| "未知".to_string());
+    ui.label(format!("大小: {:.2} MB    时长: {duration}", info.size_mb()));
+    if info.is_suspicious() {
+        ui.colored_label(
+            Color32::from_rgb(234, 179, 8),
+            "⚠ 音频文件过小 (<100 KB)，TTS 可能未正常合成",
+        );
+    }
+}
+
+/// One-line "音频已生成" confirmation for a work dir's synthesized audio.
+fn draw_audio_summary(ui: &mut egui::Ui, files: &EpisodeFiles) {
+    let Some(info) = &files.audio else {
+        ui.colored_label(Color32::from_rgb(239, 68, 68), "未找到生成的音频文件");
+        return;
+    };
+    // Prefer a duration recorded by the pipeline over our header estimate
+    let duration = files
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("duration").and_then(|v| v.as_f64()))
+        .or(info.duration_secs)
+        .map(audio::format_duration)
+        .unwrap_or_else(|| "时长未知".to_string());
+    ui.colored_label(
+        Color32::from_rgb(34, 197, 94),
+        format!("音频已生成: {duration}, {:.1} MB", info.size_mb()),
+    );
+    if info.is_suspicious() {
+        ui.colored_label(
+            Color32::from_rgb(234, 179, 8),
+            "⚠ 音频文件过小 (<100 KB)，TTS 可能未正常合成",
+        );
+    }
+}
+
+/// First audio file directly inside `dir`, for work dirs without metadata.
+fn find_audio(dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .find(|p| AudioFormat::from_path(p).is_some())
+}
+
+/// Chapter start times recorded by the audio step, which publishing lists in the article.
+fn draw_chapter_timestamps(ui: &mut egui::Ui, meta: &serde_json::Value) {
+    let Some(chapters) = meta.get("chapters").and_then(|v| v.as_array()).filter(|c| c.len() > 1) else {
+        return;
+    };
+    egui::CollapsingHeader::new(format!("章节 ({})", chapters.len()))
+        .id_salt("publish_chapters")
+        .show(ui, |ui| {
+            for chapter in chapters {
+                let title = chapter.get("title").and_then(|v| v.as_str()).unwrap_or_default();
+                let start = chapter.get("start").and_then(|v| v.as_f64()).unwrap_or_default();
+                ui.horizontal(|ui| {
+                    ui.monospace(
+                        RichText::new(audio::format_duration(start)).color(Color32::from_rgb(156, 163, 175)),
+                    );
+                    ui.label(title);
+                });
+            }
+        });
+}
+
+/// Episode audio path from metadata.json; `mp3_path` is the pre-format key.
+fn metadata_audio_path(meta: &serde_json::Value) -> Option<&str> {
+    ["audio_path", "mp3_path"].iter().find_map(|key| meta.get(key)?.as_str())
+}
+
+/// Public URL of the episode audio, with the same fallback as [`metadata_audio_path`].
+fn metadata_audio_url(meta: &serde_json::Value) -> Option<&str> {
+    ["audio_cdn_url", "mp3_cdn_url"].iter().find_map(|key| meta.get(key)?.as_str())
+}
+
+/// Whether `path` is `root` or somewhere below it, comparing resolved paths
+/// where possible so `..` and symlinks don't cause false alarms.
+fn is_inside(path: &Path, root: &Path) -> bool {
+    let resolve = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    resolve(path).starts_with(resolve(root))
+}
+
+/// Episode title from `metadata.json`, or the work dir's name.
+fn metadata_title<'a>(meta: &'a serde_json::Value, work_dir: &'a Path) -> &'a str {
+    meta.get("title")
+        .and_then(|v| v.as_str())
+        .filter(|t| !t.is_empty())
+        .or_else(|| work_dir.file_name()?.to_str())
+        .unwrap_or_default()
+}
+
+/// Buttons that copy an audio link ready to paste into notes or a web page.
+fn draw_link_formats(ui: &mut egui::Ui, title: &str, url: &str) {
+    if ui.small_button("Markdown").on_hover_text("复制为 [标题](链接)").clicked() {
+        ui.ctx().copy_text(link_markdown(title, url));
+    }
+    if ui.small_button("HTML").on_hover_text("复制为 <audio> 标签").clicked() {
+        ui.ctx().copy_text(audio_html(title, url));
+    }
+}
+
+fn link_markdown(title: &str, url: &str) -> String {
+    let title = title.replace('[', "\\[").replace(']', "\\]");
+    // Spaces and parentheses would end the link target early
+    let url = url.replace(' ', "%20").replace('(', "%28").replace(')', "%29");
+    format!("[{title}]({url})")
+}
+
+fn audio_html(title: &str, url: &str) -> String {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('"', "&quot;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    format!(
+        "<audio controls preload=\"none\" src=\"{}\" title=\"{}\"></audio>",
+        escape(url),
+        escape(title)
+    )
+}
+
+/// Most recently modified subfolders of `roots` that contain a `script.json`.
+fn find_work_dirs(roots: &[PathBuf]) -> Vec<PathBuf> {
+    const MAX_RESULTS: usize = 10;
+    let mut found: Vec<(std::time::SystemTime, PathBuf)> = roots
+        .iter()
+        .filter_map(|root| std::fs::read_dir(root).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter_map(|dir| {
+            let modified = std::fs::metadata(dir.join("script.json")).ok()?.modified().ok()?;
+            Some((modified, dir))
+        })
+        .collect();
+    found.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    // The same folder can be reached through several roots
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for (_, dir) in found {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs.truncate(MAX_RESULTS);
+    dirs
+}
+
+/// Fixed start of the work dir names `WORKDIR_TEMPLATE` produces for `stem` on `date`.
+///
+/// Rendering stops at the first placeholder other than `{date}` and `{stem}`
+/// (e.g. `{time}`), and the pipeline may append `_2`, `_3`... to avoid
+/// overwriting, so this only matches a prefix.
+fn work_dir_prefix(template: &str, date: &str, stem: &str) -> String {
+    let template = match template.trim() {
+        "" => "{date}_{stem}",
+        t => t,
+    };
+    let rendered = template.replace("{date}", date).replace("{stem}", stem);
+    match rendered.find('{') {
+        Some(i) => rendered[..i].to_string(),
+        None => rendered,
     }
 }
 
+/// Can we create files in `dir`? Checked by creating and deleting a probe file.
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".podcast-studio-write-test-{}", std::process::id()));
+    let ok = std::fs::write(&probe, b"").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    ok
+}
+
 /// Get today's date as YYYY-MM-DD string (no chrono dependency).
 fn chrono_today() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -764,6 +4518,17 @@ fn chrono_today() -> String {
     format!("{y:04}-{m:02}-{d:02}")
 }
 
+/// Current Beijing time of day as `HH:MM:SS`.
+fn clock_now() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let secs = (secs + 8 * 3600) % 86400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
 fn days_to_date(days: u64) -> (u64, u64, u64) {
     // Algorithm from http://howardhinnant.github.io/date_algorithms.html
     let z = days + 719468;
@@ -778,20 +4543,3 @@ fn days_to_date(days: u64) -> (u64, u64, u64) {
     let y = if m <= 2 { y + 1 } else { y };
     (y, m, d)
 }
-
-/// Find project root by walking up from exe dir looking for run.py.
-fn find_project_root() -> PathBuf {
-    let exe = std::env::current_exe().unwrap_or_default();
-    let mut dir = exe.parent().map(|p| p.to_path_buf()).unwrap_or_default();
-    for _ in 0..10 {
-        if dir.join("run.py").exists() {
-            return dir;
-        }
-        if let Some(parent) = dir.parent() {
-            dir = parent.to_path_buf();
-        } else {
-            break;
-        }
-    }
-    std::env::current_dir().unwrap_or_default()
-}
@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Files smaller than this are almost certainly a failed synthesis.
+pub const SUSPICIOUS_SIZE_BYTES: u64 = 100 * 1024;
+
+/// Container the audio step encodes the final episode to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    Aac,
+    Ogg,
+}
+
+impl AudioFormat {
+    pub const ALL: [AudioFormat; 3] = [AudioFormat::Mp3, AudioFormat::Aac, AudioFormat::Ogg];
+
+    /// File extension, also the value passed to `podcast-audio --format`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Aac => "aac",
+            AudioFormat::Ogg => "ogg",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "MP3",
+            AudioFormat::Aac => "AAC",
+            AudioFormat::Ogg => "OGG",
+        }
+    }
+
+    /// Format of a file, judged by its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        Self::ALL.into_iter().find(|f| f.extension().eq_ignore_ascii_case(ext))
+    }
+}
+
+/// Basic facts about a generated audio file.
+#[derive(Clone, Debug)]
+pub struct AudioInfo {
+    pub size_bytes: u64,
+    /// Duration in seconds, if the header could be parsed.
+    pub duration_secs: Option<f64>,
+}
+
+impl AudioInfo {
+    pub fn size_mb(&self) -> f64 {
+        self.size_bytes as f64 / (1024.0 * 1024.0)
+    }
+
+    pub fn is_suspicious(&self) -> bool {
+        self.size_bytes < SUSPICIOUS_SIZE_BYTES
+    }
+}
+
+/// Written by the audio step: segment file name → TTS backend that produced it.
+const SEGMENT_ENGINES_FILE: &str = "segments/engines.json";
+
+/// Which TTS engine produced each segment of the last audio run.
+pub struct SegmentEngines {
+    /// Engine label per turn index in playback order; a turn split into
+    /// several segments from different engines lists them all.
+    pub by_turn: BTreeMap<usize, String>,
+    /// Segment count per engine label, most used first.
+    pub counts: Vec<(&'static str, usize)>,
+}
+
+impl SegmentEngines {
+    /// Read `segments/engines.json`; `None` if the audio step never wrote one.
+    pub fn load(work_dir: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(work_dir.join(SEGMENT_ENGINES_FILE)).ok()?;
+        let files: BTreeMap<String, String> = serde_json::from_str(&content).ok()?;
+        let mut by_turn: BTreeMap<usize, Vec<&'static str>> = BTreeMap::new();
+        let mut counts: BTreeMap<&'static str, usize> = BTreeMap::new();
+        for (file, backend) in &files {
+            // seg_012.mp3, or seg_012_1.mp3 for the second chunk of a long turn
+            let Some(turn) = file
+                .strip_prefix("seg_")
+                .and_then(|rest| rest.split(['_', '.']).next())
+                .and_then(|n| n.parse().ok())
+            else {
+                continue;
+            };
+            let label = engine_label(backend);
+            *counts.entry(label).or_default() += 1;
+            let labels = by_turn.entry(turn).or_default();
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+        Some(Self {
+            by_turn: by_turn.into_iter().map(|(turn, labels)| (turn, labels.join("+"))).collect(),
+            counts,
+        })
+    }
+
+    /// E.g. `38 DashScope / 2 Edge`.
+    pub fn summary(&self) -> String {
+        self.counts
+            .iter()
+            .map(|(label, n)| format!("{n} {label}"))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+/// Display name for a pipeline TTS backend (`qwen_api`, `edge`, ...).
+pub fn engine_label(backend: &str) -> &'static str {
+    match backend {
+        "qwen_api" | "qwen_local" | "qwen_cloud" => "Qwen",
+        "dashscope" => "DashScope",
+        "edge" => "Edge",
+        _ => "其他",
+    }
+}
+
+/// Read file size and, for MP3, estimate the duration.
+///
+/// Only the first MP3 frame is parsed: a Xing/Info/VBRI header gives the exact
+/// frame count, otherwise the duration is estimated from the constant bitrate.
+/// Other formats report no duration.
+pub fn probe_audio(path: &Path) -> Option<AudioInfo> {
+    let size_bytes = std::fs::metadata(path).ok()?.len();
+    let duration_secs = match AudioFormat::from_path(path) {
+        Some(AudioFormat::Mp3) => mp3_duration(path, size_bytes),
+        _ => None,
+    };
+    Some(AudioInfo { size_bytes, duration_secs })
+}
+
+/// Format seconds as `m:ss` or `h:mm:ss`.
+pub fn format_duration(secs: f64) -> String {
+    let total = secs.round() as u64;
+    let (h, m, s) = (total / 3600, (total % 3600) / 60, total % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+fn mp3_duration(path: &Path, file_size: u64) -> Option<f64> {
+    let mut file = File::open(path).ok()?;
+
+    // Skip an ID3v2 tag (which may contain large cover art)
+    let mut id3 = [0u8; 10];
+    file.read_exact(&mut id3).ok()?;
+    let mut audio_start = 0u64;
+    if &id3[..3] == b"ID3" {
+        let tag_size = id3[6..10]
+            .iter()
+            .fold(0u64, |acc, b| (acc << 7) | u64::from(b & 0x7F));
+        let footer = if id3[5] & 0x10 != 0 { 10 } else { 0 };
+        audio_start = 10 + tag_size + footer;
+    }
+
+    file.seek(SeekFrom::Start(audio_start)).ok()?;
+    let mut buf = vec![0u8; 16 * 1024];
+    let n = file.read(&mut buf).ok()?;
+    let buf = &buf[..n];
+
+    let offset = (0..buf.len().saturating_sub(4))
+        .find(|&i| buf[i] == 0xFF && buf[i + 1] & 0xE0 == 0xE0 && parse_header(&buf[i..]).is_some())?;
+    let header = parse_header(&buf[offset..])?;
+    let frame = &buf[offset..];
+
+    if let Some(frames) = vbr_frame_count(frame, &header) {
+        return Some(frames as f64 * header.samples_per_frame as f64 / header.sample_rate as f64);
+    }
+
+    let audio_bytes = file_size.saturating_sub(audio_start + offset as u64);
+    Some(audio_bytes as f64 * 8.0 / (header.bitrate_kbps as f64 * 1000.0))
+}
+
+struct FrameHeader {
+    is_mpeg1: bool,
+    is_mono: bool,
+    bitrate_kbps: u32,
+    sample_rate: u32,
+    samples_per_frame: u32,
+}
+
+/// Parse an MPEG Layer III frame header.
+fn parse_header(b: &[u8]) -> Option<FrameHeader> {
+    const BITRATES_V1: [u32; 15] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320];
+    const BITRATES_V2: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+    if b.len() < 4 || b[0] != 0xFF || b[1] & 0xE0 != 0xE0 {
+        return None;
+    }
+    let version = (b[1] >> 3) & 0x03; // 0 = 2.5, 2 = 2, 3 = 1
+    let layer = (b[1] >> 1) & 0x03; // 1 = Layer III
+    if version == 1 || layer != 1 {
+        return None;
+    }
+    let is_mpeg1 = version == 3;
+
+    let bitrate_idx = (b[2] >> 4) as usize;
+    let rate_idx = ((b[2] >> 2) & 0x03) as usize;
+    if bitrate_idx == 0 || bitrate_idx == 15 || rate_idx == 3 {
+        return None;
+    }
+    let bitrate_kbps = if is_mpeg1 { BITRATES_V1[bitrate_idx] } else { BITRATES_V2[bitrate_idx] };
+    let sample_rate = match version {
+        3 => [44100, 48000, 32000][rate_idx],
+        2 => [22050, 24000, 16000][rate_idx],
+        _ => [11025, 12000, 8000][rate_idx],
+    };
+
+    Some(FrameHeader {
+        is_mpeg1,
+        is_mono: b[3] >> 6 == 0x03,
+        bitrate_kbps,
+        sample_rate,
+        samples_per_frame: if is_mpeg1 { 1152 } else { 576 },
+    })
+}
+
+/// Read the total frame count from a Xing/Info or VBRI header, if present.
+fn vbr_frame_count(frame: &[u8], header: &FrameHeader) -> Option<u32> {
+    let read_u32 = |at: usize| -> Option<u32> {
+        frame.get(at..at + 4).map(|s| u32::from_be_bytes([s[0], s[1], s[2], s[3]]))
+    };
+
+    let side_info = match (header.is_mpeg1, header.is_mono) {
+        (true, true) => 17,
+        (true, false) => 32,
+        (false, true) => 9,
+        (false, false) => 17,
+    };
+    let xing = 4 + side_info;
+    if let Some(tag) = frame.get(xing..xing + 4) {
+        if tag == b"Xing" || tag == b"Info" {
+            let flags = read_u32(xing + 4)?;
+            return if flags & 0x01 != 0 { read_u32(xing + 8) } else { None };
+        }
+    }
+
+    if frame.get(36..40) == Some(b"VBRI".as_slice()) {
+        return read_u32(36 + 14);
+    }
+    None
+}
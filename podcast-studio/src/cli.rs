@@ -0,0 +1,339 @@
+use std::path::{Path, PathBuf};
+
+use crate::audio::AudioFormat;
+use crate::pdf;
+use crate::pipeline::{self, GenerationParams, Pipeline, STEPS};
+use crate::runner::{self, Entrypoint, LogLine, PipelineEvent, Redactor};
+use crate::script::{Script, KNOWN_SPEAKERS};
+use crate::series::{self, SeriesList};
+use crate::settings::{self, Settings};
+
+const USAGE: &str = "\
+用法: podcast-studio --pdf <文件> --output-dir <目录> --run-all [选项]
+
+不带 --run-all 启动时打开图形界面。
+
+  --pdf <文件>          要转换的 PDF
+  --output-dir <目录>   工作目录的上级目录
+  --run-all             依次运行 生成剧本 → 生成音频 → 上传发布（跳过剧本编辑）
+  --profile <名称>      使用 .env.<名称> 配置
+  --format <mp3|aac|ogg>  音频格式，默认 mp3
+  --series <ID>         发布为 series.json 中该系列的下一期
+  -h, --help            显示此帮助";
+
+/// Exit code for bad arguments or a run that cannot start.
+const EXIT_USAGE: i32 = 2;
+
+struct CliArgs {
+    pdf: PathBuf,
+    output_dir: PathBuf,
+    profile: Option<String>,
+    format: AudioFormat,
+    series: Option<String>,
+}
+
+/// Do these arguments (without the program name) ask for a headless run?
+pub fn is_headless(args: &[String]) -> bool {
+    args.iter().any(|a| matches!(a.as_str(), "--run-all" | "-h" | "--help"))
+}
+
+/// Run the pipeline without the GUI for the given arguments (without the
+/// program name) and return the process exit code.
+///
+/// Step logs go to stdout/stderr as the pipeline writes them. A failed step
+/// exits with the pipeline's own exit code.
+pub fn run(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        // Scripts driving a headless run can follow these instead of the log text
+        println!("{USAGE}\n\n标准输出中的事件行 (JSON):{}", runner::EVENT_SCHEMA.trim_end());
+        return 0;
+    }
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return EXIT_USAGE;
+        }
+    };
+
+    let project_root = runner::find_project_root(&Entrypoint::from_env());
+    if let Some(profile) = &args.profile {
+        if !settings::available_profiles(&project_root).contains(profile) {
+            eprintln!("配置不存在: .env.{profile} (项目目录 {})", project_root.display());
+            return EXIT_USAGE;
+        }
+    }
+    let settings = Settings::load(&project_root, args.profile.as_deref());
+    if let Some(id) = &args.series {
+        if SeriesList::load(&project_root).get(id).is_none() {
+            eprintln!("系列不存在: {id} ({})", SeriesList::path(&project_root).display());
+            return EXIT_USAGE;
+        }
+    }
+    if let Err(e) = pdf::check_file(&args.pdf) {
+        eprintln!("{e}");
+        return EXIT_USAGE;
+    }
+
+    let mut pipeline = Pipeline::new();
+    pipeline.pdf_path = Some(args.pdf.clone());
+    pipeline.output_dir = Some(args.output_dir.clone());
+    pipeline.advance();
+
+    let runner = HeadlessRunner { project_root: &project_root, settings: &settings };
+    loop {
+        let step = pipeline.current_step;
+        let info = &STEPS[step];
+        println!(">>> [{step}/{}] {}", STEPS.len() - 1, info.name);
+        let missing = settings.missing_keys(info.required_keys);
+        if !missing.is_empty() {
+            eprintln!("缺少配置: {}", missing.join(", "));
+            return EXIT_USAGE;
+        }
+        pipeline.set_running();
+        if let Err((code, msg)) = runner.run_step(&mut pipeline, &args) {
+            eprintln!("{}失败: {msg}", info.name);
+            pipeline.fail(msg);
+            return code;
+        }
+        if step + 1 == STEPS.len() {
+            pipeline.complete_current();
+            break;
+        }
+        pipeline.advance();
+    }
+    if let Some(dir) = &pipeline.work_dir {
+        println!("全部完成: {}", dir.display());
+    }
+    0
+}
+
+fn parse_args(args: &[String]) -> Result<CliArgs, String> {
+    let (mut pdf, mut output_dir, mut profile, mut series) = (None, None, None, None);
+    let mut format = AudioFormat::Mp3;
+    let mut run_all = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().cloned().ok_or_else(|| format!("{arg} 缺少参数值"));
+        match arg.as_str() {
+            "--pdf" => pdf = Some(PathBuf::from(value()?)),
+            "--output-dir" => output_dir = Some(PathBuf::from(value()?)),
+            "--profile" => profile = Some(value()?),
+            "--series" => series = Some(value()?),
+            "--format" => {
+                let ext = value()?;
+                format = AudioFormat::ALL
+                    .into_iter()
+                    .find(|f| f.extension().eq_ignore_ascii_case(&ext))
+                    .ok_or_else(|| format!("不支持的音频格式: {ext}"))?;
+            }
+            "--run-all" => run_all = true,
+            other => return Err(format!("未知参数: {other}")),
+        }
+    }
+    // Only full runs for now; single steps stay in the GUI
+    if !run_all {
+        return Err("缺少 --run-all".to_string());
+    }
+    Ok(CliArgs {
+        pdf: pdf.ok_or("缺少 --pdf")?,
+        output_dir: output_dir.ok_or("缺少 --output-dir")?,
+        profile,
+        format,
+        series,
+    })
+}
+
+/// Spawns steps the way the GUI does, minus the interactive parts.
+struct HeadlessRunner<'a> {
+    project_root: &'a Path,
+    settings: &'a Settings,
+}
+
+impl HeadlessRunner<'_> {
+    /// Run the current step to completion. Errors carry the exit code to return.
+    fn run_step(&self, pipeline: &mut Pipeline, args: &CliArgs) -> Result<(), (i32, String)> {
+        match pipeline.current_step {
+            1 => {
+                let pdf = args.pdf.display().to_string();
+                let out = args.output_dir.display().to_string();
+                let model = self.settings.get("LLM_MODEL").trim();
+                let base_url = self.settings.get("LLM_BASE_URL").trim();
+                let template = self.settings.get("WORKDIR_TEMPLATE").trim();
+                let mut step_args = vec!["podcast-script", "--pdf", &pdf, "--output-dir", &out];
+                step_args.extend(pipeline::llm_args(model, base_url));
+                if !template.is_empty() {
+                    step_args.extend(["--name-template", template]);
+                }
+                let lines = self.spawn(&step_args, &[])?;
+                let dir = runner::work_dir_from_logs(&lines, self.project_root)
+                    .ok_or((1, "日志中未找到工作目录".to_string()))?;
+                let mut params = GenerationParams {
+                    llm_model: model.to_string(),
+                    llm_host: pipeline::url_host(base_url).to_string(),
+                    ..Default::default()
+                };
+                for line in &lines {
+                    if let Some(PipelineEvent::Tokens { prompt, completion }) = runner::parse_event(line) {
+                        params.prompt_tokens += prompt;
+                        params.completion_tokens += completion;
+                    }
+                }
+                params.save(&dir);
+                pipeline.work_dir = Some(dir);
+            }
+            // Nobody to edit the script; just make sure the audio step can read it
+            2 => {
+                let path = work_dir(pipeline)?.join("script.json");
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| (1, format!("无法读取 {}: {e}", path.display())))?;
+                let script = Script::parse(&content).map_err(|e| (1, e))?;
+                let unknown: Vec<String> = script
+                    .speakers()
+                    .into_iter()
+                    .filter(|s| !KNOWN_SPEAKERS.contains(&s.as_str()))
+                    .collect();
+                if !unknown.is_empty() {
+                    eprintln!("警告: 未知说话人 {}，生成音频时可能失败", unknown.join("、"));
+                }
+            }
+            3 => {
+                let dir = work_dir(pipeline)?.to_path_buf();
+                let dir_display = dir.display().to_string();
+                let mut step_args = vec!["podcast-audio", "--dir", &dir_display];
+                if args.format != AudioFormat::Mp3 {
+                    step_args.extend(["--format", args.format.extension()]);
+                }
+                self.spawn(&step_args, &[])?;
+                let mut params = GenerationParams::load(&dir).unwrap_or_default();
+                params.tts_engine = self.settings.tts_engine_label();
+                params.save(&dir);
+            }
+            4 => {
+                // A fresh work dir has never been uploaded
+                let dir = work_dir(pipeline)?.display().to_string();
+                let mut step_args = vec!["publish-podcast", "--podcast-dir", &dir, "--upload-audio"];
+                let mut series = SeriesList::load(self.project_root);
+                let episode = args.series.as_deref().and_then(|id| series.get(id)).map(|s| s.next_episode());
+                let episode_arg = episode.map(|n| n.to_string()).unwrap_or_default();
+                if let (Some(id), Some(_)) = (&args.series, episode) {
+                    step_args.extend(["--series", id.as_str(), "--episode", episode_arg.as_str()]);
+                }
+                self.spawn(&step_args, &[])?;
+                let published = series::published_episode(work_dir(pipeline)?);
+                if let (Some(id), Some(episode)) = (&args.series, episode) {
+                    if published != Some((id.clone(), episode)) {
+                        return Err((1, format!("发布结果中没有系列 {id} 第 {episode} 期的草稿")));
+                    }
+                    series.record_episode(id, episode);
+                    series.save(self.project_root).map_err(|e| (1, e))?;
+                    println!("已发布为系列 {id} 第 {episode} 期");
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Spawn the pipeline, echo its output and wait for it to exit.
+    fn spawn(&self, args: &[&str], envs: &[(&str, &str)]) -> Result<Vec<LogLine>, (i32, String)> {
+        let entrypoint = Entrypoint::parse(self.settings.get("PYTHON_ENTRYPOINT"));
+        println!("$ {}", entrypoint.command_line(self.project_root, args));
+        let mut all_envs = self.settings.profile_envs();
+        all_envs.extend_from_slice(envs);
+        let handle = runner::spawn_python(self.project_root, &entrypoint, args, &all_envs);
+        let groups = settings::load_setting_groups(self.project_root).unwrap_or(settings::SETTING_GROUPS);
+        let redactor = Redactor::new(self.settings.secret_values(groups));
+
+        let mut lines = Vec::new();
+        // The channel closes once the process and its output readers are done
+        for mut line in handle.rx.iter() {
+            redactor.redact(&mut line);
+            if line.is_stderr {
+                eprintln!("{}", line.text);
+            } else {
+                println!("{}", line.text);
+            }
+            lines.push(line);
+        }
+        match handle.wait() {
+            Some(status) if status.success() => Ok(lines),
+            Some(status) => {
+                let code = status.code().unwrap_or(-1);
+                Err((if code > 0 { code } else { 1 }, format!("退出码 {code}")))
+            }
+            None => Err((1, "无法启动 Python".to_string())),
+        }
+    }
+}
+
+fn work_dir(pipeline: &Pipeline) -> Result<&Path, (i32, String)> {
+    pipeline.work_dir.as_deref().ok_or((1, "没有工作目录".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn headless_only_for_cli_flags() {
+        assert!(is_headless(&args(&["--pdf", "a.pdf", "--output-dir", "out", "--run-all"])));
+        assert!(is_headless(&args(&["-h"])));
+        assert!(is_headless(&args(&["--help"])));
+        assert!(!is_headless(&args(&[])));
+        assert!(!is_headless(&args(&["/home/me/briefing.pdf"])));
+        assert!(!is_headless(&args(&["-psn_0_12345"])));
+    }
+
+    #[test]
+    fn parses_a_full_run() {
+        let parsed = parse_args(&args(&[
+            "--pdf", "a b.pdf", "--output-dir", "out", "--run-all", "--profile", "test", "--series", "series-1",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.pdf, PathBuf::from("a b.pdf"));
+        assert_eq!(parsed.output_dir, PathBuf::from("out"));
+        assert_eq!(parsed.profile.as_deref(), Some("test"));
+        assert_eq!(parsed.series.as_deref(), Some("series-1"));
+        assert_eq!(parsed.format, AudioFormat::Mp3);
+    }
+
+    #[test]
+    fn format_parsing() {
+        let base = ["--pdf", "a.pdf", "--output-dir", "out", "--run-all", "--format"];
+        let with = |ext: &str| parse_args(&args(&[&base[..], &[ext]].concat()));
+        assert_eq!(with("aac").unwrap().format, AudioFormat::Aac);
+        assert_eq!(with("OGG").unwrap().format, AudioFormat::Ogg);
+        assert_eq!(with("wav").err().unwrap(), "不支持的音频格式: wav");
+    }
+
+    #[test]
+    fn missing_values() {
+        assert_eq!(parse_args(&args(&["--run-all", "--pdf"])).err().unwrap(), "--pdf 缺少参数值");
+        assert_eq!(
+            parse_args(&args(&["--run-all", "--output-dir", "out"])).err().unwrap(),
+            "缺少 --pdf"
+        );
+        assert_eq!(parse_args(&args(&["--run-all", "--pdf", "a.pdf"])).err().unwrap(), "缺少 --output-dir");
+    }
+
+    #[test]
+    fn run_all_is_required() {
+        assert_eq!(
+            parse_args(&args(&["--pdf", "a.pdf", "--output-dir", "out"])).err().unwrap(),
+            "缺少 --run-all"
+        );
+    }
+
+    #[test]
+    fn unknown_flags_are_rejected() {
+        assert_eq!(
+            parse_args(&args(&["--pdf", "a.pdf", "--output-dir", "out", "--run-all", "--verbose"])).err().unwrap(),
+            "未知参数: --verbose"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+/// One line of a line-based diff, borrowing the compared texts or owning a copy.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiffLine<T> {
+    Same(T),
+    Removed(T),
+    Added(T),
+}
+
+impl DiffLine<&str> {
+    /// Copy the line so the diff can outlive the texts it was computed from.
+    pub fn into_owned(self) -> DiffLine<String> {
+        match self {
+            DiffLine::Same(t) => DiffLine::Same(t.to_string()),
+            DiffLine::Removed(t) => DiffLine::Removed(t.to_string()),
+            DiffLine::Added(t) => DiffLine::Added(t.to_string()),
+        }
+    }
+}
+
+/// Compute a line diff from `old` to `new` using the longest common subsequence.
+///
+/// Common leading/trailing lines are stripped first so the quadratic LCS table
+/// only covers the changed region, which keeps typical script edits cheap.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<&'a str>> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    // lcs[i][j] = LCS length of a_mid[i..] and b_mid[j..]
+    let (n, m) = (a_mid.len(), b_mid.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a_mid[i] == b_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out: Vec<DiffLine<&str>> = a[..prefix].iter().map(|&l| DiffLine::Same(l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a_mid[i] == b_mid[j] {
+            out.push(DiffLine::Same(a_mid[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(DiffLine::Removed(a_mid[i]));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(b_mid[j]));
+            j += 1;
+        }
+    }
+    out.extend(a_mid[i..].iter().map(|&l| DiffLine::Removed(l)));
+    out.extend(b_mid[j..].iter().map(|&l| DiffLine::Added(l)));
+    out.extend(a[a.len() - suffix..].iter().map(|&l| DiffLine::Same(l)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DiffLine::{Added, Removed, Same};
+
+    #[test]
+    fn identical_input() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nb\nc"), vec![Same("a"), Same("b"), Same("c")]);
+        assert_eq!(diff_lines("", ""), vec![]);
+    }
+
+    #[test]
+    fn pure_insertion() {
+        assert_eq!(diff_lines("a\nc", "a\nb\nc"), vec![Same("a"), Added("b"), Same("c")]);
+        assert_eq!(diff_lines("", "a\nb"), vec![Added("a"), Added("b")]);
+        // Repeated lines: the prefix must not overlap the suffix
+        assert_eq!(diff_lines("x\nx", "x\nx\nx"), vec![Same("x"), Same("x"), Added("x")]);
+    }
+
+    #[test]
+    fn pure_deletion() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nc"), vec![Same("a"), Removed("b"), Same("c")]);
+        assert_eq!(diff_lines("a\nb", ""), vec![Removed("a"), Removed("b")]);
+    }
+
+    #[test]
+    fn replacement_in_the_middle() {
+        assert_eq!(
+            diff_lines("a\nb\nc\nd", "a\nB\nC\nd"),
+            vec![Same("a"), Removed("b"), Removed("c"), Added("B"), Added("C"), Same("d")]
+        );
+    }
+
+    #[test]
+    fn trailing_newline_is_not_a_line() {
+        assert_eq!(diff_lines("a\nb\n", "a\nb"), vec![Same("a"), Same("b")]);
+        assert_eq!(diff_lines("a\n", "a\nb\n"), vec![Same("a"), Added("b")]);
+    }
+}
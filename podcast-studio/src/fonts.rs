@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+use eframe::egui;
+
+/// Usual CJK fonts of the platform, tried in order.
+#[cfg(target_os = "windows")]
+const SYSTEM_FONTS: &[&str] = &[
+    "C:/Windows/Fonts/msyh.ttc",    // Microsoft YaHei
+    "C:/Windows/Fonts/simhei.ttf",   // SimHei
+    "C:/Windows/Fonts/simsun.ttc",   // SimSun
+];
+
+#[cfg(target_os = "macos")]
+const SYSTEM_FONTS: &[&str] = &[
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/STHeiti Medium.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+];
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const SYSTEM_FONTS: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc", // Debian/Ubuntu
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",      // Arch
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc", // Fedora
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+];
+
+/// Font compiled into the binary when `assets/fonts/cjk-fallback.ttf` exists at build time.
+#[cfg(has_bundled_font)]
+const BUNDLED_FONT: Option<&[u8]> = Some(include_bytes!("../assets/fonts/cjk-fallback.ttf"));
+#[cfg(not(has_bundled_font))]
+const BUNDLED_FONT: Option<&[u8]> = None;
+
+/// Install a Chinese fallback font, reading it off the UI thread.
+///
+/// msyh.ttc alone is ~16 MB, so reading it in `PodcastApp::new` delays the
+/// first frame. `preferred` paths are tried before the platform's fonts, and
+/// the bundled font (if any) after them. The file that loads is sent back so
+/// it can be remembered for the next launch.
+pub fn load_in_background(ctx: &egui::Context, preferred: Vec<PathBuf>) -> mpsc::Receiver<PathBuf> {
+    let (tx, rx) = mpsc::channel();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let candidates = preferred.into_iter().chain(SYSTEM_FONTS.iter().map(PathBuf::from));
+        for path in candidates {
+            if let Ok(font_data) = std::fs::read(&path) {
+                install(&ctx, font_data);
+                let _ = tx.send(path);
+                return;
+            }
+        }
+        match BUNDLED_FONT {
+            Some(font_data) => install(&ctx, font_data.to_vec()),
+            None => eprintln!("Warning: no Chinese font found, CJK characters may not display correctly"),
+        }
+    });
+    rx
+}
+
+fn install(ctx: &egui::Context, font_data: Vec<u8>) {
+    let mut fonts = egui::FontDefinitions::default();
+    fonts.font_data.insert(
+        "chinese".to_owned(),
+        egui::FontData::from_owned(font_data).into(),
+    );
+
+    // Insert Chinese font as fallback for proportional and monospace
+    if let Some(family) = fonts.families.get_mut(&egui::FontFamily::Proportional) {
+        family.push("chinese".to_owned());
+    }
+    if let Some(family) = fonts.families.get_mut(&egui::FontFamily::Monospace) {
+        family.push("chinese".to_owned());
+    }
+
+    ctx.set_fonts(fonts);
+    ctx.request_repaint();
+}
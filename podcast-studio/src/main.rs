@@ -1,24 +1,61 @@
 mod app;
+mod audio;
+mod cli;
+mod diff;
+mod fonts;
+mod net;
+mod pdf;
 mod pipeline;
 mod runner;
+mod script;
+mod series;
 mod settings;
+mod undo;
+mod update;
 mod widgets;
 
+/// Identifies the app to the OS: the Wayland app id / X11 `WM_CLASS` that
+/// desktop environments use to group windows and match a `.desktop` file.
+const APP_ID: &str = "podcast-studio";
+
 fn main() -> eframe::Result {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format_timestamp_secs()
         .init();
+    // Other arguments (a file from a file association, macOS `-psn_...`) still open the GUI
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_headless(&args) {
+        std::process::exit(cli::run(&args));
+    }
+    let mut viewport = eframe::egui::ViewportBuilder::default()
+        .with_title("飞行播客工作站")
+        .with_app_id(APP_ID)
+        .with_inner_size([960.0, 640.0])
+        .with_min_inner_size([800.0, 500.0]);
+    if let Some(icon) = app_icon() {
+        viewport = viewport.with_icon(icon);
+    }
     let options = eframe::NativeOptions {
-        viewport: eframe::egui::ViewportBuilder::default()
-            .with_title("飞行播客工作站")
-            .with_inner_size([960.0, 640.0])
-            .with_min_inner_size([800.0, 500.0]),
+        viewport,
         ..Default::default()
     };
 
     eframe::run_native(
-        "podcast-studio",
+        APP_ID,
         options,
         Box::new(|cc| Ok(Box::new(app::PodcastApp::new(cc)))),
     )
 }
+
+/// Window/taskbar icon, embedded when `assets/icon.png` exists at build time.
+#[cfg(has_app_icon)]
+fn app_icon() -> Option<eframe::egui::IconData> {
+    eframe::icon_data::from_png_bytes(include_bytes!("../assets/icon.png"))
+        .inspect_err(|e| log::warn!("Invalid app icon: {e}"))
+        .ok()
+}
+
+#[cfg(not(has_app_icon))]
+fn app_icon() -> Option<eframe::egui::IconData> {
+    None
+}
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use crate::settings::Settings;
+
+/// Applies to the whole request, including reading the body.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Proxy for the app's own requests, falling back to the pipeline's `WECHAT_PROXY`.
+///
+/// Not `HTTP_PROXY`: the pipeline loads `.env` into its environment, where
+/// that name would reroute every request it makes.
+fn proxy_setting(settings: &Settings) -> &str {
+    match settings.get("STUDIO_PROXY").trim() {
+        "" => settings.get("WECHAT_PROXY").trim(),
+        proxy => proxy,
+    }
+}
+
+/// Blocking HTTP client that goes through [`proxy_setting`] when one is set.
+///
+/// Every request the app itself makes (as opposed to the Python pipeline)
+/// should use this.
+///
+/// Like the pipeline's curl calls, system proxy variables (`HTTPS_PROXY` etc.)
+/// are ignored, so an empty setting means a direct connection. Call it off the
+/// UI thread; requests block until done or timed out.
+///
+/// Used by the update check (`update.rs`).
+pub fn http_client(settings: &Settings) -> Result<reqwest::blocking::Client, String> {
+    let builder = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("podcast-studio/", env!("CARGO_PKG_VERSION")));
+    let proxy = proxy_setting(settings);
+    let builder = if proxy.is_empty() {
+        builder.no_proxy()
+    } else {
+        let proxy = reqwest::Proxy::all(proxy).map_err(|e| format!("代理地址无效: {e}"))?;
+        builder.proxy(proxy)
+    };
+    builder.build().map_err(|e| format!("无法创建 HTTP 客户端: {e}"))
+}
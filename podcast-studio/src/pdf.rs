@@ -0,0 +1,167 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+/// Pages with fewer visible characters than this are likely scanned images.
+const MIN_TEXT_CHARS: usize = 20;
+
+/// Length of the preview shown to the user.
+const PREVIEW_CHARS: usize = 300;
+
+/// Documents with more pages than this take noticeably long to extract.
+const LARGE_PDF_PAGES: usize = 100;
+
+/// Pages sampled, evenly spread, to extrapolate the document's token count.
+const TOKEN_SAMPLE_PAGES: usize = 8;
+
+/// Readers accept the `%PDF-` header anywhere in the first 1024 bytes.
+const HEADER_SEARCH_BYTES: u64 = 1024;
+
+/// Text found on the first page of a PDF.
+#[derive(Clone, Debug)]
+pub struct PdfPreview {
+    pub first_page_text: String,
+    pub page_count: usize,
+    /// Estimated tokens in the sampled pages, and how many pages were sampled.
+    pub sampled_tokens: usize,
+    pub sampled_pages: usize,
+}
+
+impl PdfPreview {
+    /// Does the first page have (almost) no text layer?
+    pub fn looks_image_only(&self) -> bool {
+        self.first_page_text.chars().filter(|c| !c.is_whitespace()).count() < MIN_TEXT_CHARS
+    }
+
+    /// Will text extraction for this document be slow?
+    pub fn is_large(&self) -> bool {
+        self.page_count > LARGE_PDF_PAGES
+    }
+
+    /// Rough LLM token count of the whole document, extrapolated from the sample.
+    pub fn estimated_tokens(&self) -> usize {
+        if self.sampled_pages == 0 {
+            return 0;
+        }
+        self.sampled_tokens * self.page_count / self.sampled_pages
+    }
+
+    /// A short, whitespace-collapsed excerpt of the first page.
+    pub fn excerpt(&self) -> String {
+        let collapsed = self.first_page_text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut excerpt: String = collapsed.chars().take(PREVIEW_CHARS).collect();
+        if collapsed.chars().count() > PREVIEW_CHARS {
+            excerpt.push('…');
+        }
+        excerpt
+    }
+}
+
+/// Background text extraction for one PDF.
+pub struct PdfProbe {
+    pub path: PathBuf,
+    rx: Option<mpsc::Receiver<Result<PdfPreview, String>>>,
+    result: Option<Result<PdfPreview, String>>,
+}
+
+impl PdfProbe {
+    /// Start extracting the first page's text off the UI thread.
+    pub fn start(path: &Path) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let owned = path.to_path_buf();
+        thread::spawn(move || {
+            let _ = tx.send(extract_first_page(&owned));
+        });
+        Self {
+            path: path.to_path_buf(),
+            rx: Some(rx),
+            result: None,
+        }
+    }
+
+    /// The extraction result, or `None` while still running.
+    pub fn poll(&mut self) -> Option<&Result<PdfPreview, String>> {
+        if let Some(rx) = &self.rx {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.result = Some(result);
+                    self.rx = None;
+                }
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.result = Some(Err("解析线程意外退出".to_string()));
+                    self.rx = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
+        }
+        self.result.as_ref()
+    }
+
+    /// Page count, once extraction has succeeded.
+    pub fn page_count(&self) -> Option<usize> {
+        self.result.as_ref()?.as_ref().ok().map(|p| p.page_count)
+    }
+}
+
+/// Quick check that `path` is a readable, non-empty file with a PDF header.
+///
+/// Catches e.g. an HTML error page saved as `.pdf` before anything is run on it.
+pub fn check_file(path: &Path) -> Result<(), String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("无法读取文件: {e}"))?;
+    let mut head = Vec::new();
+    file.by_ref()
+        .take(HEADER_SEARCH_BYTES)
+        .read_to_end(&mut head)
+        .map_err(|e| format!("无法读取文件: {e}"))?;
+    if head.is_empty() {
+        return Err("文件是空的 (0 字节)".to_string());
+    }
+    if !head.windows(5).any(|w| w == b"%PDF-") {
+        let looks_html = String::from_utf8_lossy(&head).to_ascii_lowercase().contains("<html");
+        return Err(if looks_html {
+            "这不是 PDF，而是网页 (HTML)，可能是下载链接失效".to_string()
+        } else {
+            "这不是有效的 PDF 文件 (缺少 %PDF 文件头)".to_string()
+        });
+    }
+    Ok(())
+}
+
+fn extract_first_page(path: &Path) -> Result<PdfPreview, String> {
+    let doc = lopdf::Document::load(path).map_err(|e| format!("无法解析 PDF: {e}"))?;
+    let pages = doc.get_pages();
+    let numbers: Vec<u32> = pages.keys().copied().collect();
+    let first = *numbers.first().ok_or("PDF 没有页面")?;
+    // Pages without text content (scans) may error instead of returning ""
+    let first_page_text = doc.extract_text(&[first]).unwrap_or_default();
+
+    let step = numbers.len().div_ceil(TOKEN_SAMPLE_PAGES);
+    let sample: Vec<u32> = numbers.iter().step_by(step).copied().collect();
+    let sampled_tokens = sample
+        .iter()
+        .map(|&n| estimate_tokens(&doc.extract_text(&[n]).unwrap_or_default()))
+        .sum();
+    Ok(PdfPreview {
+        first_page_text,
+        page_count: pages.len(),
+        sampled_tokens,
+        sampled_pages: sample.len(),
+    })
+}
+
+/// Ballpark token count: about one token per CJK character and one per four
+/// other non-whitespace characters, in line with common BPE tokenizers.
+fn estimate_tokens(text: &str) -> usize {
+    let (cjk, other) = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .fold((0usize, 0usize), |(cjk, other), c| {
+            if ('\u{4E00}'..='\u{9FFF}').contains(&c) || ('\u{3000}'..='\u{303F}').contains(&c) {
+                (cjk + 1, other)
+            } else {
+                (cjk, other + 1)
+            }
+        });
+    cjk + other.div_ceil(4)
+}
@@ -1,41 +1,62 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
 
 /// Status of a single pipeline step.
 #[derive(Clone, Debug, PartialEq)]
 pub enum StepStatus {
     Pending,
+    /// Scheduled to run but not yet spawned.
+    Queued,
     Running,
     Done,
     Failed(String),
 }
 
 impl StepStatus {
+    /// Finished, so a run-all can move past it. A queued step has not
+    /// started yet and is no more terminal than a running one.
     pub fn is_terminal(&self) -> bool {
-        matches!(self, StepStatus::Done | StepStatus::Failed(_))
+        match self {
+            StepStatus::Done | StepStatus::Failed(_) => true,
+            StepStatus::Pending | StepStatus::Queued | StepStatus::Running => false,
+        }
     }
 }
 
 /// Human-readable info for each step.
 pub struct StepInfo {
     pub name: &'static str,
+    /// Shown as the timeline row's tooltip.
     pub description: &'static str,
+    /// Settings keys that must be non-empty before the step can run.
+    pub required_keys: &'static [&'static str],
 }
 
-pub const STEPS: [StepInfo; 5] = [
-    StepInfo { name: "选择 PDF",  description: "选择要转换的 PDF 文件" },
-    StepInfo { name: "生成剧本", description: "调用 LLM 生成对话剧本" },
-    StepInfo { name: "编辑剧本", description: "查看和编辑 script.json" },
-    StepInfo { name: "生成音频", description: "TTS 合成 + 音频拼接" },
-    StepInfo { name: "上传发布", description: "上传到 R2 并创建微信草稿" },
+/// Pipeline steps in order; everything sized per step follows this list.
+pub const STEPS: &[StepInfo] = &[
+    StepInfo { name: "选择 PDF",  description: "选择要转换的 PDF 文件",     required_keys: &[] },
+    StepInfo { name: "生成剧本", description: "调用 LLM 生成对话剧本",     required_keys: &["LLM_API_KEY", "LLM_BASE_URL", "LLM_MODEL"] },
+    StepInfo { name: "编辑剧本", description: "查看和编辑 script.json",   required_keys: &[] },
+    StepInfo { name: "生成音频", description: "TTS 合成 + 音频拼接",      required_keys: &["DASHSCOPE_API_KEY"] },
+    StepInfo { name: "上传发布", description: "上传到 R2 并创建微信草稿", required_keys: &["WECHAT_APP_ID", "WECHAT_APP_SECRET", "R2_DOMAIN"] },
 ];
 
-/// The 5-step podcast pipeline state.
+/// The podcast pipeline state, one entry per [`STEPS`] item.
 pub struct Pipeline {
     pub pdf_path: Option<PathBuf>,
     pub output_dir: Option<PathBuf>,
     pub work_dir: Option<PathBuf>,
-    pub steps: [StepStatus; 5],
+    pub steps: Vec<StepStatus>,
     pub current_step: usize,
+    /// Number of times each step has been started.
+    pub attempts: Vec<u32>,
+    /// Error messages from each step's failed attempts, oldest first.
+    pub errors: Vec<Vec<String>>,
+    /// TTS segment progress `(done, total)` reported by the audio step.
+    pub segment_progress: Option<(usize, usize)>,
+    /// Fraction of the running step done, from the pipeline's progress events.
+    pub progress: Option<f32>,
 }
 
 impl Pipeline {
@@ -44,14 +65,12 @@ impl Pipeline {
             pdf_path: None,
             output_dir: None,
             work_dir: None,
-            steps: [
-                StepStatus::Pending,
-                StepStatus::Pending,
-                StepStatus::Pending,
-                StepStatus::Pending,
-                StepStatus::Pending,
-            ],
+            steps: vec![StepStatus::Pending; STEPS.len()],
             current_step: 0,
+            attempts: vec![0; STEPS.len()],
+            errors: vec![Vec::new(); STEPS.len()],
+            segment_progress: None,
+            progress: None,
         }
     }
 
@@ -61,26 +80,109 @@ impl Pipeline {
 
     /// Advance to the next step after completing the current one.
     pub fn advance(&mut self) {
-        if self.current_step < 4 {
+        if self.current_step + 1 < self.steps.len() {
             self.steps[self.current_step] = StepStatus::Done;
             self.current_step += 1;
         }
     }
 
     pub fn fail(&mut self, msg: String) {
+        self.errors[self.current_step].push(msg.clone());
         self.steps[self.current_step] = StepStatus::Failed(msg);
     }
 
     pub fn set_running(&mut self) {
         self.steps[self.current_step] = StepStatus::Running;
+        self.attempts[self.current_step] += 1;
+        self.segment_progress = None;
+        self.progress = None;
     }
 
     pub fn complete_current(&mut self) {
         self.steps[self.current_step] = StepStatus::Done;
     }
 
+    /// Number of steps that have completed.
+    pub fn done_count(&self) -> usize {
+        self.steps.iter().filter(|s| **s == StepStatus::Done).count()
+    }
+
     /// Can the user retry the current step?
     pub fn can_retry(&self) -> bool {
         matches!(self.steps[self.current_step], StepStatus::Failed(_))
     }
 }
+
+/// Settings that produced an episode, recorded in `work_dir/generation.json`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GenerationParams {
+    #[serde(default)]
+    pub llm_model: String,
+    #[serde(default)]
+    pub llm_host: String,
+    #[serde(default)]
+    pub tts_engine: String,
+    /// LLM token usage of the script step, if the pipeline reported it.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub prompt_tokens: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub completion_tokens: u64,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+impl GenerationParams {
+    const FILE_NAME: &'static str = "generation.json";
+
+    pub fn load(work_dir: &Path) -> Option<Self> {
+        std::fs::read_to_string(work_dir.join(Self::FILE_NAME))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    pub fn save(&self, work_dir: &Path) {
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(work_dir.join(Self::FILE_NAME), json);
+        }
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// Estimated LLM cost given a price per 1000 tokens.
+    pub fn estimated_cost(&self, price_per_1k: f64) -> f64 {
+        self.total_tokens() as f64 / 1000.0 * price_per_1k
+    }
+
+    /// One-line summary for display.
+    pub fn summary(&self) -> String {
+        let or_unknown = |s: &str| if s.is_empty() { "未知".to_string() } else { s.to_string() };
+        format!(
+            "模型 {} · {} · TTS {}",
+            or_unknown(&self.llm_model),
+            or_unknown(&self.llm_host),
+            or_unknown(&self.tts_engine),
+        )
+    }
+}
+
+/// `--model`/`--base-url` for the script step; empty settings are left to the pipeline's own.
+pub fn llm_args<'a>(model: &'a str, base_url: &'a str) -> Vec<&'a str> {
+    let mut args = Vec::new();
+    if !model.is_empty() {
+        args.extend(["--model", model]);
+    }
+    if !base_url.is_empty() {
+        args.extend(["--base-url", base_url]);
+    }
+    args
+}
+
+/// Host part of a URL, e.g. `api.openai.com` for `https://api.openai.com/v1/...`.
+pub fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, r)| r);
+    rest.split(['/', '?', '#']).next().unwrap_or(rest)
+}
@@ -1,41 +1,410 @@
 use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::process::{Command, ExitStatus, Stdio};
-use std::sync::mpsc;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Severity parsed from a Python logging prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] =
+        [LogLevel::Debug, LogLevel::Info, LogLevel::Warning, LogLevel::Error, LogLevel::Critical];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARNING",
+            LogLevel::Error => "ERROR",
+            LogLevel::Critical => "CRITICAL",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARNING" | "WARN" => Some(LogLevel::Warning),
+            "ERROR" => Some(LogLevel::Error),
+            "CRITICAL" | "FATAL" => Some(LogLevel::Critical),
+            _ => None,
+        }
+    }
+
+    /// Detect the level of a Python log line.
+    ///
+    /// Handles both the default `INFO:root:msg` format and the project's
+    /// `2026-03-09 12:00:00 INFO [name] msg` formatter.
+    pub fn parse(text: &str) -> Option<Self> {
+        let trimmed = text.trim_start();
+        if let Some((head, _)) = trimmed.split_once(':') {
+            if let Some(level) = Self::from_name(head) {
+                return Some(level);
+            }
+        }
+        trimmed.split_whitespace().take(3).find_map(Self::from_name)
+    }
+}
+
+/// Structured events the pipeline writes to stdout, one JSON object per line.
+/// `flying_podcast.core.events` on the Python side follows this schema.
+pub const EVENT_SCHEMA: &str = r#"
+{"event": "progress", "pct": 0.4}              fraction of the current step, 0.0-1.0
+{"event": "phase", "name": "上传音频"}          start of a named phase
+{"event": "segment", "done": 12, "total": 48}  TTS segments synthesized so far
+{"event": "tokens", "prompt": 1234, "completion": 567}  LLM usage of one call
+{"event": "work_dir", "path": "/abs/work/dir"} work directory of the episode
+"#;
+
+/// A typed event from the pipeline, see [`EVENT_SCHEMA`].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PipelineEvent {
+    Progress { pct: f32 },
+    Phase { name: String },
+    Segment { done: usize, total: usize },
+    Tokens {
+        #[serde(default)]
+        prompt: u64,
+        #[serde(default)]
+        completion: u64,
+    },
+    WorkDir { path: PathBuf },
+}
+
+impl PipelineEvent {
+    /// Parse a stdout line as an event; anything else is plain log text.
+    fn from_json_line(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if !text.starts_with("{\"event\"") {
+            return None;
+        }
+        serde_json::from_str(text).ok()
+    }
+}
+
+/// The event a log line carries: a JSON event line, or one of the older
+/// text markers the pipeline may still print.
+pub fn parse_event(line: &LogLine) -> Option<PipelineEvent> {
+    if let Some(event) = &line.event {
+        return Some(event.clone());
+    }
+    let text = line.text.as_str();
+    if let Some((done, total)) = parse_segment_progress(text) {
+        return Some(PipelineEvent::Segment { done, total });
+    }
+    if let Some((prompt, completion)) = parse_token_usage(text) {
+        return Some(PipelineEvent::Tokens { prompt, completion });
+    }
+    if let Some(name) = parse_phase(text) {
+        return Some(PipelineEvent::Phase { name: name.to_string() });
+    }
+    WORK_DIR_MARKERS.iter().find_map(|marker| {
+        let path = text.split_once(marker)?.1.trim();
+        (!path.is_empty()).then(|| PipelineEvent::WorkDir { path: PathBuf::from(path) })
+    })
+}
+
+/// Parse a TTS progress marker like `SEGMENT 12/48 speaker=A` into `(12, 48)`.
+///
+/// The marker may be preceded by a logging prefix; garbled counts are ignored.
+fn parse_segment_progress(text: &str) -> Option<(usize, usize)> {
+    let rest = text.split_once("SEGMENT ")?.1;
+    let counts = rest.split_whitespace().next()?;
+    let (done, total) = counts.split_once('/')?;
+    let (done, total) = (done.parse().ok()?, total.parse().ok()?);
+    (total > 0 && done <= total).then_some((done, total))
+}
+
+/// Parse an LLM usage line like `TOKENS prompt=1234 completion=5678`.
+///
+/// Either count may be missing and is then taken as zero.
+fn parse_token_usage(text: &str) -> Option<(u64, u64)> {
+    let rest = text.split_once("TOKENS ")?.1;
+    let (mut prompt, mut completion) = (None, None);
+    for part in rest.split_whitespace() {
+        match part.split_once('=') {
+            Some(("prompt", n)) => prompt = n.parse().ok(),
+            Some(("completion", n)) => completion = n.parse().ok(),
+            _ => {}
+        }
+    }
+    if prompt.is_none() && completion.is_none() {
+        return None;
+    }
+    Some((prompt.unwrap_or(0), completion.unwrap_or(0)))
+}
+
+/// Parse a phase marker like `>>> PHASE: 提取文本` into its name.
+fn parse_phase(text: &str) -> Option<&str> {
+    let name = text.split_once(">>> PHASE:")?.1.trim();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Markers after which the script step logs its work directory.
+const WORK_DIR_MARKERS: &[&str] = &["Output dir:", "Script generation complete:"];
+
+/// The work directory the script step reported, newest mention first;
+/// only directories that exist count.
+///
+/// The pipeline runs in `project_root` (see [`spawn_python`]), so relative
+/// paths are taken from there rather than from the app's own directory.
+pub fn work_dir_from_logs(lines: &[LogLine], project_root: &Path) -> Option<PathBuf> {
+    lines.iter().rev().find_map(|line| match parse_event(line)? {
+        PipelineEvent::WorkDir { path } => Some(project_root.join(path)).filter(|p| p.is_dir()),
+        _ => None,
+    })
+}
+
+/// First line of a Python traceback as printed by the interpreter.
+const TRACEBACK_HEADER: &str = "Traceback (most recent call last):";
+
+/// Line ranges of Python tracebacks in the log.
+///
+/// A traceback runs from its header through the exception line, the first
+/// unindented line after the indented frames. One still being written runs to
+/// the end of the log.
+pub fn find_tracebacks(lines: &[LogLine]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if !lines[i].text.trim_start().starts_with(TRACEBACK_HEADER) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        while i < lines.len() && lines[i].text.starts_with([' ', '\t']) {
+            i += 1;
+        }
+        let end = (i + 1).min(lines.len());
+        ranges.push(start..end);
+        i = end;
+    }
+    ranges
+}
+
+/// A recognizable failure whose fix is usually a setting.
+pub struct ErrorHint {
+    /// Pipeline step the signature applies to.
+    pub step: usize,
+    /// Case-insensitive signatures; numeric codes only match as whole words.
+    pub patterns: &'static [&'static str],
+    /// Setting to jump to.
+    pub key: &'static str,
+    pub message: &'static str,
+}
+
+/// Known error signatures, checked in order.
+pub const ERROR_HINTS: &[ErrorHint] = &[
+    ErrorHint { step: 1, patterns: &["401", "unauthorized", "invalid api key", "incorrect api key", "403 forbidden"], key: "LLM_API_KEY",       message: "检测到 LLM 鉴权错误，点击前往设置" },
+    ErrorHint { step: 1, patterns: &["model_not_found", "model not found", "404 not found"],                         key: "LLM_MODEL",         message: "检测到模型或接口地址错误，点击前往设置" },
+    ErrorHint { step: 3, patterns: &["401", "unauthorized", "invalidapikey", "invalid api-key", "403 forbidden"],    key: "DASHSCOPE_API_KEY", message: "检测到 TTS 鉴权错误，点击前往设置" },
+    ErrorHint { step: 4, patterns: &["invalid appid", "40013", "invalid appsecret", "40125", "40164", "not in whitelist"], key: "WECHAT_APP_ID", message: "检测到微信公众号鉴权错误，点击前往设置" },
+    ErrorHint { step: 4, patterns: &["401", "403 forbidden"],                                                        key: "R2_DOMAIN",         message: "检测到鉴权错误，点击前往设置" },
+];
+
+/// Find the first known error signature for `step` in the log.
+pub fn detect_error_hint(step: usize, lines: &[LogLine]) -> Option<&'static ErrorHint> {
+    let lowered: Vec<String> = lines.iter().map(|l| l.text.to_lowercase()).collect();
+    ERROR_HINTS.iter().filter(|h| h.step == step).find(|hint| {
+        hint.patterns
+            .iter()
+            .any(|p| lowered.iter().any(|line| contains_word(line, p)))
+    })
+}
+
+/// Does `haystack` contain `needle` not embedded in a longer alphanumeric run?
+/// Keeps a bare "401" from matching inside timestamps or byte counts.
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(at, _)| {
+        let before = haystack[..at].chars().next_back();
+        let after = haystack[at + needle.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric()) && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
 
 /// A single log line from the subprocess.
 #[derive(Clone, Debug)]
 pub struct LogLine {
     pub text: String,
     pub is_stderr: bool,
+    /// Level detected from the logging prefix, if any.
+    pub level: Option<LogLevel>,
+    /// Set for JSON event lines on stdout, which are not shown as log text.
+    pub event: Option<PipelineEvent>,
+}
+
+impl LogLine {
+    pub fn new(text: String, is_stderr: bool) -> Self {
+        let level = LogLevel::parse(&text);
+        let event = if is_stderr { None } else { PipelineEvent::from_json_line(&text) };
+        Self { text, is_stderr, level, event }
+    }
+}
+
+/// Values shorter than this are never masked; they would mangle ordinary words.
+const MIN_SECRET_LEN: usize = 6;
+
+/// Masks known secret values in log lines before they are stored, so they
+/// never reach the log panel, saved logs, exports or the clipboard.
+#[derive(Default)]
+pub struct Redactor {
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    pub fn new<'a>(secrets: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .map(str::trim)
+            .filter(|s| s.len() >= MIN_SECRET_LEN)
+            .map(String::from)
+            .collect();
+        // Longest first, so a secret containing another one is masked whole
+        secrets.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        secrets.dedup();
+        Self { secrets }
+    }
+
+    pub fn redact(&self, line: &mut LogLine) {
+        for secret in &self.secrets {
+            if line.text.contains(secret.as_str()) {
+                line.text = line.text.replace(secret.as_str(), "****");
+            }
+        }
+    }
 }
 
 /// Handle to a running Python subprocess.
 pub struct RunHandle {
     pub rx: mpsc::Receiver<LogLine>,
     pub join: Option<thread::JoinHandle<Option<ExitStatus>>>,
+    /// The child process, shared with the waiter thread so it can be killed.
+    child: Arc<Mutex<Option<Child>>>,
+    /// Set when a kill was requested, in case the child was not spawned yet.
+    killed: Arc<AtomicBool>,
 }
 
 impl RunHandle {
     /// Check if the subprocess has finished. Returns `Some(status)` if done.
     pub fn try_finish(&mut self) -> Option<ExitStatus> {
-        if self.join.as_ref().map_or(true, |j| j.is_finished()) {
+        if self.is_finished() {
             self.join.take().and_then(|j| j.join().ok().flatten())
         } else {
             None
         }
     }
+
+    /// Whether the waiter thread is done. It joins both readers first, so by
+    /// then every line the subprocess printed has been sent to `rx`.
+    pub fn is_finished(&self) -> bool {
+        self.join.as_ref().is_none_or(|j| j.is_finished())
+    }
+
+    /// Kill the child process and whatever it started. The waiter thread then observes the exit.
+    pub fn kill(&self) {
+        self.killed.store(true, Ordering::SeqCst);
+        if let Ok(mut slot) = self.child.lock() {
+            if let Some(child) = slot.as_mut() {
+                kill_tree(child);
+            }
+        }
+    }
+
+    /// Block until the subprocess exits; `None` if it could not be spawned.
+    pub fn wait(mut self) -> Option<ExitStatus> {
+        self.join.take().and_then(|j| j.join().ok().flatten())
+    }
+
+    /// Kill the child and wait for the waiter and reader threads to finish.
+    pub fn shutdown(mut self) {
+        self.kill();
+        if let Some(join) = self.join.take() {
+            let _ = join.join();
+        }
+    }
+}
+
+/// Default pipeline entrypoint, relative to the project root.
+pub const DEFAULT_ENTRYPOINT: &str = "run.py";
+
+/// How the Python pipeline is invoked: a script file or `-m module`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Entrypoint {
+    Script(PathBuf),
+    Module(String),
+}
+
+impl Entrypoint {
+    /// Parse a configured entrypoint; empty means [`DEFAULT_ENTRYPOINT`].
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Some(module) = value.strip_prefix("-m") {
+            let module = module.trim();
+            if !module.is_empty() {
+                return Entrypoint::Module(module.to_string());
+            }
+        }
+        if value.is_empty() {
+            Entrypoint::Script(PathBuf::from(DEFAULT_ENTRYPOINT))
+        } else {
+            Entrypoint::Script(PathBuf::from(value))
+        }
+    }
+
+    /// Entrypoint from the `PYTHON_ENTRYPOINT` environment variable, used for
+    /// root detection before `.env` can be read.
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("PYTHON_ENTRYPOINT").unwrap_or_default())
+    }
+
+    /// The command line [`spawn_python`] runs for `args`, for display.
+    pub fn command_line(&self, root: &Path, args: &[&str]) -> String {
+        let mut parts = vec!["python".to_string()];
+        match self {
+            Entrypoint::Script(path) => parts.push(root.join(path).display().to_string()),
+            Entrypoint::Module(module) => parts.extend(["-m".to_string(), module.clone()]),
+        }
+        parts.extend(args.iter().map(|a| if a.contains(' ') { format!("\"{a}\"") } else { a.to_string() }));
+        parts.join(" ")
+    }
+
+    /// Does `dir` look like the project root for this entrypoint?
+    fn found_in(&self, dir: &Path) -> bool {
+        match self {
+            Entrypoint::Script(path) => dir.join(path).is_file(),
+            Entrypoint::Module(module) => {
+                // Top-level package either at the root or in a src/ layout
+                let package = module.split('.').next().unwrap_or(module);
+                dir.join(package).is_dir() || dir.join("src").join(package).is_dir()
+            }
+        }
+    }
 }
 
-/// Locate the project root (parent of podcast-studio/).
-fn project_root() -> std::path::PathBuf {
+/// Locate the project root by walking up from the executable until the
+/// entrypoint is found, falling back to the current working directory.
+pub fn find_project_root(entrypoint: &Entrypoint) -> PathBuf {
     let exe = std::env::current_exe().unwrap_or_default();
     // During development, exe is in target/debug or target/release
-    // Walk up until we find run.py
     let mut dir = exe.parent().map(|p| p.to_path_buf()).unwrap_or_default();
     for _ in 0..10 {
-        if dir.join("run.py").exists() {
+        if entrypoint.found_in(&dir) {
             return dir;
         }
         if let Some(parent) = dir.parent() {
@@ -44,32 +413,51 @@ fn project_root() -> std::path::PathBuf {
             break;
         }
     }
-    // Fallback: current working directory
     std::env::current_dir().unwrap_or_default()
 }
 
 /// Spawn a Python command in the background, streaming stdout/stderr to a channel.
-pub fn spawn_python(args: &[&str]) -> RunHandle {
-    let root = project_root();
+///
+/// `envs` override values the pipeline would otherwise read from `.env`.
+pub fn spawn_python(
+    root: &Path,
+    entrypoint: &Entrypoint,
+    args: &[&str],
+    envs: &[(&str, &str)],
+) -> RunHandle {
+    let root = root.to_path_buf();
+    let entry_args: Vec<std::ffi::OsString> = match entrypoint {
+        Entrypoint::Script(path) => vec![root.join(path).into_os_string()],
+        Entrypoint::Module(module) => vec!["-m".into(), module.into()],
+    };
     let args_owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    let envs_owned: Vec<(String, String)> = envs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
     let (tx, rx) = mpsc::channel();
+    let child_slot: Arc<Mutex<Option<Child>>> = Arc::new(Mutex::new(None));
+    let child_shared = Arc::clone(&child_slot);
+    let killed = Arc::new(AtomicBool::new(false));
+    let killed_shared = Arc::clone(&killed);
 
     let join = thread::spawn(move || {
         let mut cmd = Command::new("python");
-        cmd.arg(root.join("run.py"))
+        cmd.args(&entry_args)
             .args(&args_owned)
             .current_dir(&root)
+            .envs(envs_owned)
             .env("PYTHONUNBUFFERED", "1")
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
+        // A group of its own, so a kill also reaches the ffmpeg and other tools it runs
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
 
         let mut child = match cmd.spawn() {
             Ok(c) => c,
             Err(e) => {
-                let _ = tx.send(LogLine {
-                    text: format!("Failed to spawn Python: {e}"),
-                    is_stderr: true,
-                });
+                let _ = tx.send(LogLine::new(format!("Failed to spawn Python: {e}"), true));
                 return None;
             }
         };
@@ -79,10 +467,8 @@ pub fn spawn_python(args: &[&str]) -> RunHandle {
         let tx_out = tx.clone();
         let stdout_thread = thread::spawn(move || {
             if let Some(out) = stdout {
-                for line in BufReader::new(out).lines() {
-                    if let Ok(line) = line {
-                        let _ = tx_out.send(LogLine { text: line, is_stderr: false });
-                    }
+                for line in BufReader::new(out).lines().map_while(Result::ok) {
+                    let _ = tx_out.send(LogLine::new(line, false));
                 }
             }
         });
@@ -92,15 +478,32 @@ pub fn spawn_python(args: &[&str]) -> RunHandle {
         let tx_err = tx.clone();
         let stderr_thread = thread::spawn(move || {
             if let Some(err) = stderr {
-                for line in BufReader::new(err).lines() {
-                    if let Ok(line) = line {
-                        let _ = tx_err.send(LogLine { text: line, is_stderr: true });
-                    }
+                for line in BufReader::new(err).lines().map_while(Result::ok) {
+                    let _ = tx_err.send(LogLine::new(line, true));
                 }
             }
         });
 
-        let status = child.wait().ok();
+        // Poll instead of blocking in wait() so the handle can lock the child to kill it
+        if let Ok(mut slot) = child_shared.lock() {
+            *slot = Some(child);
+        }
+        let status = loop {
+            let polled = match child_shared.lock() {
+                Ok(mut slot) => slot.as_mut().map(|c| {
+                    if killed_shared.load(Ordering::SeqCst) {
+                        kill_tree(c);
+                    }
+                    c.try_wait()
+                }),
+                Err(_) => None,
+            };
+            match polled {
+                Some(Ok(Some(status))) => break Some(status),
+                Some(Ok(None)) => thread::sleep(Duration::from_millis(50)),
+                _ => break None,
+            }
+        };
         let _ = stdout_thread.join();
         let _ = stderr_thread.join();
         status
@@ -109,7 +512,35 @@ pub fn spawn_python(args: &[&str]) -> RunHandle {
     RunHandle {
         rx,
         join: Some(join),
+        child: child_slot,
+        killed,
+    }
+}
+
+/// Kill `child` with its descendants. Tools left running would hold the output
+/// pipes open, and with them the reader threads.
+fn kill_tree(child: &mut Child) {
+    // Once reaped, the pid and its group may belong to someone else
+    if !matches!(child.try_wait(), Ok(None)) {
+        return;
+    }
+    #[cfg(unix)]
+    {
+        // The child leads its own process group, whose id is its pid
+        let _ = Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", child.id())])
+            .stderr(Stdio::null())
+            .status();
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &child.id().to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
     }
+    let _ = child.kill();
 }
 
 /// Open a file in the system default editor.
@@ -128,6 +559,77 @@ pub fn open_in_editor(path: &Path) {
     }
 }
 
+/// Reveal a file in the OS file manager, selecting it where supported.
+pub fn reveal_in_file_manager(path: &Path) {
+    #[cfg(target_os = "windows")]
+    {
+        let mut arg = std::ffi::OsString::from("/select,");
+        arg.push(path);
+        let _ = Command::new("explorer").arg(arg).spawn();
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let _ = Command::new("open").arg("-R").arg(path).spawn();
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // xdg-open has no "select" mode; open the containing folder instead
+        let dir = path.parent().unwrap_or(path);
+        let _ = Command::new("xdg-open").arg(dir).spawn();
+    }
+}
+
+/// Open a file with a user-configured editor command.
+///
+/// `{file}` in the command is replaced by the path; without a placeholder the
+/// path is appended as the last argument. Double quotes group arguments with spaces.
+pub fn open_with_command(command: &str, path: &Path) -> Result<(), String> {
+    let mut parts = split_command_line(command);
+    if parts.is_empty() {
+        return Err("编辑器命令为空".to_string());
+    }
+    let file = path.display().to_string();
+    if parts.iter().any(|p| p.contains("{file}")) {
+        for part in &mut parts {
+            *part = part.replace("{file}", &file);
+        }
+    } else {
+        parts.push(file);
+    }
+
+    let program = &parts[0];
+    Command::new(program)
+        .args(&parts[1..])
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => format!("找不到编辑器命令: {program}"),
+            _ => format!("启动编辑器失败 ({program}): {e}"),
+        })
+}
+
+/// Split a command line on whitespace, keeping double-quoted segments together.
+fn split_command_line(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in command.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
 /// Open a file specifically in VS Code.
 pub fn open_in_vscode(path: &Path) {
     #[cfg(target_os = "windows")]
@@ -142,3 +644,199 @@ pub fn open_in_vscode(path: &Path) {
         let _ = Command::new("code").arg(path).spawn();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_from_default_logging_prefix() {
+        assert_eq!(LogLevel::parse("ERROR:root:upload failed"), Some(LogLevel::Error));
+        assert_eq!(LogLevel::parse("WARNING:tts:retry in 2s"), Some(LogLevel::Warning));
+        assert_eq!(LogLevel::parse("INFO:root:done"), Some(LogLevel::Info));
+    }
+
+    #[test]
+    fn level_from_project_formatter() {
+        assert_eq!(LogLevel::parse("2026-03-09 12:00:00 ERROR [podcast] boom"), Some(LogLevel::Error));
+        assert_eq!(LogLevel::parse("2026-03-09 12:00:00 WARNING [tts] slow"), Some(LogLevel::Warning));
+        assert_eq!(LogLevel::parse("2026-03-09 12:00:00 INFO [podcast] Loaded script"), Some(LogLevel::Info));
+    }
+
+    #[test]
+    fn traceback_lines_have_no_level() {
+        let lines: Vec<LogLine> = [
+            "Traceback (most recent call last):",
+            "  File \"run.py\", line 3, in <module>",
+            "ValueError: bad pct",
+        ]
+        .into_iter()
+        .map(|text| LogLine::new(text.to_string(), true))
+        .collect();
+        assert!(lines.iter().all(|line| line.level.is_none()));
+        assert_eq!(find_tracebacks(&lines), vec![0..3]);
+    }
+
+    #[test]
+    fn plain_lines_have_no_level() {
+        assert_eq!(LogLevel::parse("Synthesizing 48 dialogue segments..."), None);
+        assert_eq!(LogLevel::parse(""), None);
+        // A level word later in the message is not a prefix
+        assert_eq!(LogLevel::parse("upload finished without any ERROR"), None);
+    }
+
+    fn event_of(text: &str) -> Option<PipelineEvent> {
+        parse_event(&LogLine::new(text.to_string(), false))
+    }
+
+    #[test]
+    fn json_event_lines() {
+        assert_eq!(event_of(r#"{"event": "progress", "pct": 0.4}"#), Some(PipelineEvent::Progress { pct: 0.4 }));
+        assert_eq!(
+            event_of(r#"{"event": "phase", "name": "上传音频"}"#),
+            Some(PipelineEvent::Phase { name: "上传音频".to_string() })
+        );
+        assert_eq!(
+            event_of(r#"{"event": "segment", "done": 12, "total": 48}"#),
+            Some(PipelineEvent::Segment { done: 12, total: 48 })
+        );
+        assert_eq!(
+            event_of(r#"{"event": "tokens", "prompt": 1234}"#),
+            Some(PipelineEvent::Tokens { prompt: 1234, completion: 0 })
+        );
+        assert_eq!(
+            event_of(r#"  {"event": "work_dir", "path": "/abs/work/dir"}  "#),
+            Some(PipelineEvent::WorkDir { path: PathBuf::from("/abs/work/dir") })
+        );
+        // Event lines are not log text
+        assert!(LogLine::new(r#"{"event": "progress", "pct": 1.0}"#.to_string(), false).event.is_some());
+        assert!(LogLine::new(r#"{"event": "progress", "pct": 1.0}"#.to_string(), true).event.is_none());
+    }
+
+    #[test]
+    fn non_json_lines_are_not_events() {
+        assert_eq!(event_of("INFO:root:Loaded script: 48 lines"), None);
+        assert_eq!(event_of(""), None);
+        assert_eq!(event_of(r#"{"event": "progress", "pct": 0.4"#), None);
+        assert_eq!(event_of(r#"{"other": 1}"#), None);
+    }
+
+    #[test]
+    fn unknown_events_are_ignored() {
+        assert_eq!(event_of(r#"{"event": "celebrate", "pct": 1.0}"#), None);
+        assert_eq!(event_of(r#"{"event": "segment", "done": 3}"#), None);
+    }
+
+    #[test]
+    fn malformed_pct_is_ignored() {
+        assert_eq!(event_of(r#"{"event": "progress", "pct": "40%"}"#), None);
+        assert_eq!(event_of(r#"{"event": "progress", "pct": null}"#), None);
+        assert_eq!(event_of(r#"{"event": "progress"}"#), None);
+    }
+
+    #[test]
+    fn text_marker_fallbacks() {
+        assert_eq!(parse_segment_progress("INFO:tts:SEGMENT 12/48 speaker=A"), Some((12, 48)));
+        assert_eq!(parse_segment_progress("SEGMENT 49/48"), None);
+        assert_eq!(parse_segment_progress("SEGMENT 1/0"), None);
+        assert_eq!(parse_segment_progress("SEGMENT x/48"), None);
+        assert_eq!(parse_token_usage("TOKENS prompt=1234 completion=5678"), Some((1234, 5678)));
+        assert_eq!(parse_token_usage("TOKENS completion=5"), Some((0, 5)));
+        assert_eq!(parse_token_usage("TOKENS prompt=abc"), None);
+        assert_eq!(event_of(">>> PHASE: 提取文本"), Some(PipelineEvent::Phase { name: "提取文本".to_string() }));
+        assert_eq!(
+            event_of("Script generation complete: /abs/dir"),
+            Some(PipelineEvent::WorkDir { path: PathBuf::from("/abs/dir") })
+        );
+    }
+
+    #[test]
+    fn split_command_line_keeps_quoted_paths() {
+        assert_eq!(
+            split_command_line(r#""C:\Program Files\Sublime Text\subl.exe" --wait "D:\my episodes\script.json""#),
+            vec![
+                r"C:\Program Files\Sublime Text\subl.exe".to_string(),
+                "--wait".to_string(),
+                r"D:\my episodes\script.json".to_string(),
+            ]
+        );
+        assert_eq!(split_command_line("  subl   {file} "), vec!["subl", "{file}"]);
+    }
+
+    #[test]
+    fn split_command_line_empty() {
+        assert!(split_command_line("").is_empty());
+        assert!(split_command_line("   \t ").is_empty());
+    }
+
+    #[test]
+    fn entrypoint_parse() {
+        assert_eq!(Entrypoint::parse(""), Entrypoint::Script(PathBuf::from(DEFAULT_ENTRYPOINT)));
+        assert_eq!(Entrypoint::parse("  "), Entrypoint::Script(PathBuf::from(DEFAULT_ENTRYPOINT)));
+        assert_eq!(Entrypoint::parse("scripts/pipeline.py"), Entrypoint::Script(PathBuf::from("scripts/pipeline.py")));
+        assert_eq!(
+            Entrypoint::parse(" -m flying_podcast.cli "),
+            Entrypoint::Module("flying_podcast.cli".to_string())
+        );
+        assert_eq!(
+            Entrypoint::parse("-m flying_podcast.cli").command_line(Path::new("/repo"), &["podcast-audio", "my dir"]),
+            r#"python -m flying_podcast.cli podcast-audio "my dir""#
+        );
+    }
+
+    /// Fresh directory under the system temp dir for one test.
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("podcast-studio-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn stdout_lines(texts: &[&str]) -> Vec<LogLine> {
+        texts.iter().map(|text| LogLine::new(text.to_string(), false)).collect()
+    }
+
+    #[test]
+    fn work_dir_relative_to_project_root() {
+        let root = temp_root("relative-work-dir");
+        let relative = Path::new("data").join("output").join("podcast").join("2026-03-09_demo");
+        std::fs::create_dir_all(root.join(&relative)).unwrap();
+
+        let lines = stdout_lines(&[&format!("Output dir: {}", relative.display()), "Script generation done"]);
+        assert_eq!(work_dir_from_logs(&lines, &root), Some(root.join(&relative)));
+
+        // Not resolved against the app's own working directory
+        let missing = stdout_lines(&["Output dir: data/output/podcast/not-there"]);
+        assert_eq!(work_dir_from_logs(&missing, &root), None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn work_dir_absolute_path_kept() {
+        let root = temp_root("absolute-work-dir");
+        let elsewhere = temp_root("absolute-work-dir-episode");
+        let event = serde_json::json!({"event": "work_dir", "path": elsewhere}).to_string();
+        assert_eq!(work_dir_from_logs(&stdout_lines(&[&event]), &root), Some(elsewhere.clone()));
+        std::fs::remove_dir_all(&root).unwrap();
+        std::fs::remove_dir_all(&elsewhere).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_reaches_tools_the_pipeline_started() {
+        let root = temp_root("kill-tree");
+        // The grandchild inherits stdout, so the reader only ends once it is gone too
+        let script = "import subprocess, time\nsubprocess.Popen(['sleep', '30'])\nprint('ready', flush=True)\ntime.sleep(30)\n";
+        std::fs::write(root.join("run.py"), script).unwrap();
+        let handle = spawn_python(&root, &Entrypoint::Script(PathBuf::from("run.py")), &[], &[]);
+        let ready = handle.rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(ready.text, "ready");
+
+        let started = std::time::Instant::now();
+        handle.kill();
+        let status = handle.wait();
+        assert!(status.is_some_and(|s| !s.success()));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
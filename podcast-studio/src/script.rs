@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Roles the pipeline's TTS has a built-in voice for (`_dashscope_voice_map`
+/// in `tts_client.py`).
+pub const KNOWN_SPEAKERS: &[&str] = &["千羽", "虎机长", "女", "男"];
+
+/// One dialogue turn in script.json.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Turn {
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emotion: Option<String>,
+    /// Fields we don't edit, kept so saving doesn't drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A titled group of turns (the "chapters" format).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Chapter {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub dialogue: Vec<Turn>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// One entry of the script outline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineEntry {
+    pub title: String,
+    /// Index of the section's first turn in playback order (see [`Script::turns`]).
+    pub first_turn: usize,
+}
+
+/// Typed view of script.json.
+///
+/// Mirrors the two formats accepted by the pipeline's `normalize_dialogue`:
+/// `{"chapters": [{"title", "dialogue": [...]}]}` and the older flat
+/// `{"dialogue": [...]}`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Script {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dialogue: Vec<Turn>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Script {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        serde_json::from_str(content).map_err(|e| format!("script.json 格式错误: {e}"))
+    }
+
+    /// All turns in playback order, regardless of format.
+    pub fn turns(&self) -> impl Iterator<Item = &Turn> {
+        self.chapters
+            .iter()
+            .flat_map(|c| c.dialogue.iter())
+            .chain(self.dialogue.iter())
+    }
+
+    /// Render as a readable document: `# title`, `## chapter` headings and
+    /// `**role**: text` lines in Markdown, or plain `role: text` lines.
+    pub fn to_readable(&self, markdown: bool) -> String {
+        let mut out = String::new();
+        let title = if self.title.trim().is_empty() { "(无标题)" } else { self.title.trim() };
+        if markdown {
+            out.push_str(&format!("# {title}\n\n"));
+        } else {
+            out.push_str(&format!("{title}\n\n"));
+        }
+        for (i, chapter) in self.chapters.iter().enumerate() {
+            let heading = if chapter.title.trim().is_empty() {
+                format!("第 {} 章", i + 1)
+            } else {
+                chapter.title.trim().to_string()
+            };
+            if markdown {
+                out.push_str(&format!("## {heading}\n\n"));
+            } else {
+                out.push_str(&format!("【{heading}】\n\n"));
+            }
+            push_turns(&mut out, &chapter.dialogue, markdown);
+        }
+        push_turns(&mut out, &self.dialogue, markdown);
+        out
+    }
+
+    /// Section headings: the chapters, or for the flat format the points where
+    /// a turn's `chapter` field changes.
+    pub fn outline(&self) -> Vec<OutlineEntry> {
+        let mut outline = Vec::new();
+        let mut first_turn = 0;
+        for (i, chapter) in self.chapters.iter().enumerate() {
+            let title = if chapter.title.trim().is_empty() {
+                format!("第 {} 章", i + 1)
+            } else {
+                chapter.title.trim().to_string()
+            };
+            outline.push(OutlineEntry { title, first_turn });
+            first_turn += chapter.dialogue.len();
+        }
+        let mut current: Option<&str> = None;
+        for (i, turn) in self.dialogue.iter().enumerate() {
+            let Some(chapter) = turn.extra.get("chapter").and_then(Value::as_str) else {
+                continue;
+            };
+            if current != Some(chapter) {
+                outline.push(OutlineEntry { title: chapter.to_string(), first_turn: first_turn + i });
+                current = Some(chapter);
+            }
+        }
+        outline
+    }
+
+    /// Distinct speaker roles in order of first appearance.
+    pub fn speakers(&self) -> Vec<String> {
+        let mut speakers: Vec<String> = Vec::new();
+        for turn in self.turns() {
+            if !turn.role.is_empty() && !speakers.contains(&turn.role) {
+                speakers.push(turn.role.clone());
+            }
+        }
+        speakers
+    }
+}
+
+fn push_turns(out: &mut String, turns: &[Turn], markdown: bool) {
+    for turn in turns {
+        let role = if turn.role.is_empty() { "?" } else { turn.role.as_str() };
+        let text = turn.text.trim();
+        if markdown {
+            // Blank line between turns so each renders as its own paragraph
+            out.push_str(&format!("**{role}**: {text}\n\n"));
+        } else {
+            out.push_str(&format!("{role}: {text}\n"));
+        }
+    }
+    if !markdown && !turns.is_empty() {
+        out.push('\n');
+    }
+}
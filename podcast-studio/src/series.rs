@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings;
+
+/// Shared with `publish-podcast --series`, which reads the templates from it.
+const FILE_NAME: &str = "series.json";
+
+/// Placeholders the Python publish step fills in `title_template`.
+pub const TITLE_PLACEHOLDERS: &str = "{series} 系列名, {episode} 期号, {title} 本期标题";
+
+/// A recurring podcast series; episodes published into it share its metadata.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Series {
+    /// Stable key passed as `--series`; the name can change.
+    pub id: String,
+    pub name: String,
+    /// Draft title, e.g. `{series} 第{episode}期：{title}`; empty keeps the episode's title.
+    #[serde(default)]
+    pub title_template: String,
+    /// Draft author when the episode doesn't set one.
+    #[serde(default)]
+    pub author: String,
+    /// Cover used when the work dir has no `cover.jpg`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<PathBuf>,
+    /// Number of the last episode published into the series.
+    #[serde(default)]
+    pub last_episode: u32,
+}
+
+impl Series {
+    pub fn next_episode(&self) -> u32 {
+        self.last_episode + 1
+    }
+}
+
+/// Series id and episode number the publish step wrote to the work dir's
+/// `publish_result.json`; only a draft that was actually created has one.
+pub fn published_episode(work_dir: &Path) -> Option<(String, u32)> {
+    let text = std::fs::read_to_string(work_dir.join("publish_result.json")).ok()?;
+    let result: serde_json::Value = serde_json::from_str(&text).ok()?;
+    let series = result.get("series")?.as_str()?.to_string();
+    let episode = u32::try_from(result.get("episode")?.as_u64()?).ok()?;
+    Some((series, episode))
+}
+
+/// All series, stored in `series.json` in the project root.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SeriesList {
+    #[serde(default)]
+    pub series: Vec<Series>,
+}
+
+impl SeriesList {
+    pub fn path(project_root: &Path) -> PathBuf {
+        project_root.join(FILE_NAME)
+    }
+
+    /// Missing or unreadable files load as an empty list.
+    pub fn load(project_root: &Path) -> Self {
+        std::fs::read_to_string(Self::path(project_root))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, project_root: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        settings::write_atomic(&Self::path(project_root), json.as_bytes())
+            .map_err(|e| format!("无法保存 {FILE_NAME}: {e}"))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Series> {
+        self.series.iter().find(|s| s.id == id)
+    }
+
+    /// Add a series with a fresh id and return that id.
+    pub fn add(&mut self, name: &str) -> String {
+        let id = (1..)
+            .map(|n| format!("series-{n}"))
+            .find(|id| self.get(id).is_none())
+            .unwrap_or_default();
+        self.series.push(Series {
+            id: id.clone(),
+            name: name.trim().to_string(),
+            title_template: "{series} 第{episode}期：{title}".to_string(),
+            ..Default::default()
+        });
+        id
+    }
+
+    /// Record a published episode, keeping the highest number seen.
+    pub fn record_episode(&mut self, id: &str, episode: u32) -> bool {
+        match self.series.iter_mut().find(|s| s.id == id) {
+            Some(series) if episode > series.last_episode => {
+                series.last_episode = episode;
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,10 +1,58 @@
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// Type of a setting field.
 pub enum FieldType {
     Text { is_secret: bool, placeholder: &'static str },
     Toggle,
+    /// Pick one of a fixed list; an empty value means the pipeline default.
+    Select { options: &'static [&'static str] },
+}
+
+/// DashScope / Qwen TTS voices usable for the dialogue speakers.
+pub const TTS_VOICES: &[&str] = &["Cherry", "Serena", "Chelsie", "Ethan", "Dylan", "Jada", "Sunny"];
+
+/// Fallback engine toggles; at least one must stay enabled.
+pub const TTS_ENGINE_TOGGLES: [&str; 2] = ["TTS_ENABLE_DASHSCOPE", "TTS_ENABLE_EDGE"];
+
+/// Keys that are only required while the given toggle is enabled.
+const CONDITIONAL_KEYS: &[(&str, &str)] = &[("DASHSCOPE_API_KEY", "TTS_ENABLE_DASHSCOPE")];
+
+/// Voice settings passed to the audio step, keyed by the env var the pipeline reads.
+pub const TTS_VOICE_KEYS: &[&str] = &["TTS_VOICE_FEMALE", "TTS_VOICE_MALE"];
+
+/// TTS engine chosen for a single audio run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TtsEngine {
+    /// Follow the fallback chain configured in settings.
+    Auto,
+    Qwen,
+    DashScope,
+    Edge,
+}
+
+impl TtsEngine {
+    pub const ALL: [TtsEngine; 4] = [TtsEngine::Auto, TtsEngine::Qwen, TtsEngine::DashScope, TtsEngine::Edge];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TtsEngine::Auto => "自动",
+            TtsEngine::Qwen => "Qwen",
+            TtsEngine::DashScope => "DashScope",
+            TtsEngine::Edge => "Edge",
+        }
+    }
+
+    /// Backend name understood by the pipeline's `TTS_FORCE_BACKEND`.
+    pub fn backend(self) -> Option<&'static str> {
+        match self {
+            TtsEngine::Auto => None,
+            TtsEngine::Qwen => Some("qwen_api"),
+            TtsEngine::DashScope => Some("dashscope"),
+            TtsEngine::Edge => Some("edge"),
+        }
+    }
 }
 
 /// A setting field displayed in the settings UI.
@@ -20,11 +68,15 @@ pub const SETTING_GROUPS: &[(&str, &[SettingField])] = &[
         SettingField { key: "LLM_API_KEY",  label: "API Key",  field_type: FieldType::Text { is_secret: true,  placeholder: "sk-..." } },
         SettingField { key: "LLM_BASE_URL", label: "Base URL", field_type: FieldType::Text { is_secret: false, placeholder: "https://api.openai.com/v1/chat/completions" } },
         SettingField { key: "LLM_MODEL",    label: "Model",    field_type: FieldType::Text { is_secret: false, placeholder: "gpt-4o" } },
+        SettingField { key: "LLM_PRICE_PER_1K", label: "每 1K tokens 价格", field_type: FieldType::Text { is_secret: false, placeholder: "0.002  (留空不估算费用)" } },
+        SettingField { key: "LLM_PRICE_TABLE",  label: "按模型价格", field_type: FieldType::Text { is_secret: false, placeholder: "gpt-4o=0.005, deepseek-chat=0.001" } },
     ]),
     ("语音合成 (TTS)", &[
         SettingField { key: "TTS_ENABLE_DASHSCOPE", label: "启用付费 DashScope",  field_type: FieldType::Toggle },
         SettingField { key: "DASHSCOPE_API_KEY",    label: "DashScope API Key",   field_type: FieldType::Text { is_secret: true, placeholder: "sk-..." } },
         SettingField { key: "TTS_ENABLE_EDGE",      label: "启用 Edge TTS (备用)", field_type: FieldType::Toggle },
+        SettingField { key: "TTS_VOICE_FEMALE",     label: "女声 (千羽)",         field_type: FieldType::Select { options: TTS_VOICES } },
+        SettingField { key: "TTS_VOICE_MALE",       label: "男声 (虎机长)",        field_type: FieldType::Select { options: TTS_VOICES } },
     ]),
     ("微信公众号", &[
         SettingField { key: "WECHAT_APP_ID",     label: "App ID",     field_type: FieldType::Text { is_secret: false, placeholder: "" } },
@@ -34,50 +86,271 @@ pub const SETTING_GROUPS: &[(&str, &[SettingField])] = &[
     ("R2 存储", &[
         SettingField { key: "R2_DOMAIN", label: "域名", field_type: FieldType::Text { is_secret: false, placeholder: "ccar.hudawang.cn" } },
     ]),
+    ("Python", &[
+        SettingField { key: "PYTHON_ENTRYPOINT", label: "入口", field_type: FieldType::Text { is_secret: false, placeholder: "run.py 或 -m package.module" } },
+        SettingField { key: "WORKDIR_TEMPLATE",  label: "工作目录命名", field_type: FieldType::Text { is_secret: false, placeholder: "{date}_{stem}  (可用 {time})" } },
+    ]),
+    ("编辑器", &[
+        SettingField { key: "EDITOR_COMMAND", label: "编辑器命令", field_type: FieldType::Text { is_secret: false, placeholder: "subl {file}  (留空使用 VS Code / 系统默认)" } },
+        SettingField { key: "SCRIPT_AUTOSAVE_SECONDS", label: "自动保存间隔(秒)", field_type: FieldType::Text { is_secret: false, placeholder: "30  (0 关闭；写入 script.json.autosave)" } },
+    ]),
+    ("网络", &[
+        SettingField { key: "STUDIO_PROXY", label: "本程序代理", field_type: FieldType::Text { is_secret: false, placeholder: "留空沿用微信代理地址；只影响本程序自身的请求" } },
+    ]),
+    ("界面", &[
+        SettingField { key: "CJK_FONT_PATH", label: "中文字体文件", field_type: FieldType::Text { is_secret: false, placeholder: "留空自动查找系统字体，重启后生效" } },
+    ]),
 ];
 
+/// Settings groups as shown in the UI: `(group name, fields)`.
+pub type SettingGroups = &'static [(&'static str, &'static [SettingField])];
+
+/// Optional schema file in the project root overriding [`SETTING_GROUPS`].
+pub const SCHEMA_FILE: &str = "podcast-settings-schema.json";
+
+#[derive(serde::Deserialize)]
+struct SchemaFile {
+    groups: Vec<SchemaGroup>,
+}
+
+#[derive(serde::Deserialize)]
+struct SchemaGroup {
+    name: String,
+    fields: Vec<SchemaField>,
+}
+
+#[derive(serde::Deserialize)]
+struct SchemaField {
+    key: String,
+    #[serde(default)]
+    label: String,
+    /// `text` (default), `toggle` or `select`.
+    #[serde(default, rename = "type")]
+    field_type: Option<String>,
+    #[serde(default)]
+    secret: bool,
+    #[serde(default)]
+    placeholder: String,
+    #[serde(default)]
+    options: Vec<String>,
+}
+
+/// Load the settings schema from [`SCHEMA_FILE`], falling back to the built-in
+/// groups when the file is absent.
+///
+/// The schema is read once at startup, so its strings are leaked to fit the
+/// `'static` field structures shared with the built-in table.
+pub fn load_setting_groups(project_root: &Path) -> Result<SettingGroups, String> {
+    let path = project_root.join(SCHEMA_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SETTING_GROUPS),
+        Err(e) => return Err(format!("无法读取 {SCHEMA_FILE}: {e}")),
+    };
+    let schema: SchemaFile =
+        serde_json::from_str(&content).map_err(|e| format!("{SCHEMA_FILE} 格式错误: {e}"))?;
+
+    let leak = |s: String| -> &'static str { Box::leak(s.into_boxed_str()) };
+    let mut groups = Vec::with_capacity(schema.groups.len());
+    for group in schema.groups {
+        let mut fields = Vec::with_capacity(group.fields.len());
+        for field in group.fields {
+            let field_type = match field.field_type.as_deref().unwrap_or("text") {
+                "text" => FieldType::Text {
+                    is_secret: field.secret,
+                    placeholder: leak(field.placeholder),
+                },
+                "toggle" => FieldType::Toggle,
+                "select" => {
+                    let options: Vec<&'static str> = field.options.into_iter().map(leak).collect();
+                    FieldType::Select { options: Box::leak(options.into_boxed_slice()) }
+                }
+                other => return Err(format!("{SCHEMA_FILE}: {} 的类型 \"{other}\" 无效", field.key)),
+            };
+            let label = if field.label.is_empty() { field.key.clone() } else { field.label };
+            fields.push(SettingField {
+                key: leak(field.key),
+                label: leak(label),
+                field_type,
+            });
+        }
+        groups.push((leak(group.name), &*Box::leak(fields.into_boxed_slice())));
+    }
+    Ok(Box::leak(groups.into_boxed_slice()))
+}
+
 /// In-memory key-value store backed by .env file.
 pub struct Settings {
     pub values: BTreeMap<String, String>,
     pub env_path: PathBuf,
+    /// Active named profile; `None` is the plain `.env`.
+    pub profile: Option<String>,
     pub dirty: bool,
     /// Track which secret fields are being shown
     pub visible_secrets: std::collections::HashSet<String>,
+    /// Modification time of the .env file when it was last loaded or saved.
+    loaded_mtime: Option<SystemTime>,
+    /// Values as last loaded or saved, for per-field change tracking.
+    saved: BTreeMap<String, String>,
 }
 
 impl Settings {
-    /// Load settings from the project's .env file.
-    pub fn load(project_root: &Path) -> Self {
-        let env_path = project_root.join(".env");
+    /// Load settings from the project's .env file, or `.env.<profile>` for a named profile.
+    pub fn load(project_root: &Path, profile: Option<&str>) -> Self {
+        let env_path = profile_path(project_root, profile);
         let values = if env_path.exists() {
             parse_env_file(&env_path)
         } else {
             BTreeMap::new()
         };
+        let loaded_mtime = file_mtime(&env_path);
         Self {
+            saved: values.clone(),
             values,
             env_path,
+            profile: profile.map(str::to_string),
             dirty: false,
             visible_secrets: std::collections::HashSet::new(),
+            loaded_mtime,
         }
     }
 
+    /// Re-read the .env file, discarding in-memory edits.
+    pub fn reload(&mut self) {
+        self.values = if self.env_path.exists() {
+            parse_env_file(&self.env_path)
+        } else {
+            BTreeMap::new()
+        };
+        self.saved = self.values.clone();
+        self.dirty = false;
+        self.loaded_mtime = file_mtime(&self.env_path);
+    }
+
+    /// Has the .env file been modified by someone else since we loaded or saved it?
+    pub fn changed_on_disk(&self) -> bool {
+        file_mtime(&self.env_path) != self.loaded_mtime
+    }
+
+    /// Accept the current on-disk version as seen without reloading it.
+    pub fn acknowledge_disk_change(&mut self) {
+        self.loaded_mtime = file_mtime(&self.env_path);
+    }
+
     pub fn get(&self, key: &str) -> &str {
         self.values.get(key).map(|s| s.as_str()).unwrap_or("")
     }
 
+    /// Values of the fields `groups` marks as secret, for masking them in logs.
+    pub fn secret_values(&self, groups: SettingGroups) -> Vec<&str> {
+        groups
+            .iter()
+            .flat_map(|(_, fields)| fields.iter())
+            .filter(|f| matches!(f.field_type, FieldType::Text { is_secret: true, .. }))
+            .map(|f| self.get(f.key))
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+
+    /// Environment overrides for a pipeline run. The pipeline only loads
+    /// `.env` itself, so a named profile is passed in full; the plain `.env`
+    /// needs nothing.
+    pub fn profile_envs(&self) -> Vec<(&str, &str)> {
+        if self.profile.is_none() {
+            return Vec::new();
+        }
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+    }
+
     pub fn get_bool(&self, key: &str) -> bool {
         matches!(self.get(key).to_lowercase().as_str(), "true" | "1" | "yes")
     }
 
+    /// Price per 1K tokens for the configured `LLM_MODEL`: its entry in
+    /// `LLM_PRICE_TABLE`, else `LLM_PRICE_PER_1K`. `None` when neither is set.
+    pub fn llm_price_per_1k(&self) -> Option<f64> {
+        let model = self.get("LLM_MODEL").trim();
+        parse_price_table(self.get("LLM_PRICE_TABLE"))
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(model))
+            .map(|(_, price)| price)
+            .or_else(|| self.get("LLM_PRICE_PER_1K").trim().parse().ok())
+    }
+
+    /// Human-readable name of the TTS engines enabled in settings.
+    pub fn tts_engine_label(&self) -> String {
+        match (self.get_bool("TTS_ENABLE_DASHSCOPE"), self.get_bool("TTS_ENABLE_EDGE")) {
+            (true, true) => "DashScope + Edge".to_string(),
+            (true, false) => "DashScope".to_string(),
+            (false, true) => "Edge".to_string(),
+            (false, false) => "默认".to_string(),
+        }
+    }
+
+    /// Required keys that are currently empty, skipping ones whose toggle is off.
+    pub fn missing_keys(&self, required: &[&'static str]) -> Vec<&'static str> {
+        required
+            .iter()
+            .copied()
+            .filter(|key| {
+                CONDITIONAL_KEYS
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .is_none_or(|(_, toggle)| self.get_bool(toggle))
+            })
+            .filter(|key| self.get(key).trim().is_empty())
+            .collect()
+    }
+
+    /// Would turning off this toggle leave no TTS fallback engine enabled?
+    pub fn is_last_tts_engine(&self, key: &str) -> bool {
+        TTS_ENGINE_TOGGLES.contains(&key)
+            && self.get_bool(key)
+            && TTS_ENGINE_TOGGLES.iter().filter(|k| self.get_bool(k)).count() == 1
+    }
+
+    /// Cross-field problems to show under a settings group.
+    pub fn group_warnings(&self, fields: &[SettingField]) -> Vec<&'static str> {
+        let mut warnings = Vec::new();
+        // Keyed on the fields rather than the group name so custom schemas still get the checks
+        if fields.iter().any(|f| TTS_ENGINE_TOGGLES.contains(&f.key)) {
+            if !TTS_ENGINE_TOGGLES.iter().any(|k| self.get_bool(k)) {
+                warnings.push("未启用任何备用 TTS 引擎，主引擎失败时音频生成将直接失败");
+            }
+            if self.get_bool("TTS_ENABLE_DASHSCOPE") && self.get("DASHSCOPE_API_KEY").trim().is_empty() {
+                warnings.push("已启用 DashScope，但 DashScope API Key 为空");
+            }
+        }
+        warnings
+    }
+
     pub fn set(&mut self, key: &str, value: String) {
         let old = self.values.get(key).cloned().unwrap_or_default();
         if old != value {
             self.values.insert(key.to_string(), value);
-            self.dirty = true;
+            // Typing a value back to what's on disk makes the field clean again
+            self.dirty = self.has_changes();
         }
     }
 
+    /// Does `key` differ from the value last loaded or saved?
+    pub fn is_changed(&self, key: &str) -> bool {
+        self.get(key) != self.saved.get(key).map(|s| s.as_str()).unwrap_or("")
+    }
+
+    /// Restore one field to its last loaded or saved value.
+    pub fn revert(&mut self, key: &str) {
+        match self.saved.get(key) {
+            Some(value) => self.values.insert(key.to_string(), value.clone()),
+            None => self.values.remove(key),
+        };
+        self.dirty = self.has_changes();
+    }
+
+    fn has_changes(&self) -> bool {
+        self.values.keys().chain(self.saved.keys()).any(|key| self.is_changed(key))
+    }
+
     pub fn set_bool(&mut self, key: &str, value: bool) {
         self.set(key, if value { "true" } else { "false" }.to_string());
     }
@@ -120,12 +393,86 @@ impl Settings {
         }
 
         let result = output_lines.join("\n") + "\n";
-        std::fs::write(&self.env_path, result).map_err(|e| format!("保存失败: {e}"))?;
+        write_atomic(&self.env_path, result.as_bytes()).map_err(|e| format!("保存失败: {e}"))?;
+        self.saved = self.values.clone();
         self.dirty = false;
+        self.loaded_mtime = file_mtime(&self.env_path);
         Ok(())
     }
 }
 
+/// Write `contents` to a temp file beside `path`, then rename it over `path`,
+/// so readers (and the next launch after a crash) never see a partial file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let file_name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{file_name}.tmp"));
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
+
+/// Parse a price table like `gpt-4o=0.005, deepseek-chat=0.001`.
+///
+/// Entries may be separated by commas or semicolons; malformed ones are skipped.
+pub fn parse_price_table(value: &str) -> Vec<(String, f64)> {
+    value
+        .split([',', ';'])
+        .filter_map(|entry| {
+            let (model, price) = entry.split_once('=')?;
+            let model = model.trim();
+            let price = price.trim().parse().ok()?;
+            (!model.is_empty()).then(|| (model.to_string(), price))
+        })
+        .collect()
+}
+
+/// Path of the env file backing a profile.
+fn profile_path(project_root: &Path, profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => project_root.join(format!(".env.{name}")),
+        None => project_root.join(".env"),
+    }
+}
+
+/// Named profiles available as `.env.<name>` files in the project root.
+pub fn available_profiles(project_root: &Path) -> Vec<String> {
+    let mut profiles: Vec<String> = std::fs::read_dir(project_root)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.strip_prefix(".env.").map(str::to_string)
+        })
+        // .env.example is the template shipped with the repo, not a profile
+        .filter(|name| !name.is_empty() && name != "example")
+        .collect();
+    profiles.sort();
+    profiles
+}
+
+/// Create a new profile file seeded with the contents of `from`.
+pub fn create_profile(project_root: &Path, name: &str, from: &Path) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name != "example"
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err("配置名只能包含字母、数字、- 和 _".to_string());
+    }
+    let path = profile_path(project_root, Some(name));
+    if path.exists() {
+        return Err(format!("配置 {name} 已存在"));
+    }
+    let content = std::fs::read_to_string(from).unwrap_or_default();
+    std::fs::write(&path, content).map_err(|e| format!("创建失败: {e}"))
+}
+
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
 /// Parse a .env file into key-value pairs.
 fn parse_env_file(path: &Path) -> BTreeMap<String, String> {
     let mut map = BTreeMap::new();
@@ -144,3 +491,49 @@ fn parse_env_file(path: &Path) -> BTreeMap<String, String> {
     }
     map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn write_atomic_never_exposes_partial_file() {
+        let dir = std::env::temp_dir().join(format!("podcast-studio-atomic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("podcast-studio.json");
+        // Large enough that a plain write would be observable half-done
+        let old = vec![b'a'; 1 << 20];
+        let new = vec![b'b'; 1 << 20];
+        write_atomic(&path, &old).unwrap();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (path, old, new, done) = (path.clone(), old.clone(), new.clone(), done.clone());
+            std::thread::spawn(move || {
+                let mut reads = 0;
+                while !done.load(Ordering::Relaxed) || reads == 0 {
+                    let seen = std::fs::read(&path).unwrap();
+                    assert!(seen == old || seen == new, "partial file of {} bytes", seen.len());
+                    reads += 1;
+                }
+            })
+        };
+        for i in 0..50 {
+            write_atomic(&path, if i % 2 == 0 { &new } else { &old }).unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+        reader.join().unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), old);
+        let leftovers: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name())
+            .filter(|name| *name != "podcast-studio.json")
+            .collect();
+        assert!(leftovers.is_empty(), "temp files left behind: {leftovers:?}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
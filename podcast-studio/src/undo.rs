@@ -0,0 +1,87 @@
+use std::time::{Duration, Instant};
+
+/// Edits closer together than this become one undo step.
+const EDIT_DEBOUNCE: Duration = Duration::from_millis(800);
+/// Oldest snapshots are dropped beyond this many.
+const MAX_DEPTH: usize = 50;
+
+/// Undo/redo of a text buffer by whole snapshots.
+///
+/// egui's own `TextEdit` undo is per widget and loses its history when the
+/// buffer is replaced; this keeps snapshots of the buffer itself.
+#[derive(Default)]
+pub struct TextHistory {
+    undo: Vec<String>,
+    redo: Vec<String>,
+    /// The text as of the last snapshot boundary.
+    base: String,
+    /// Start of the current burst of edits, not yet an undo step.
+    editing_since: Option<Instant>,
+}
+
+impl TextHistory {
+    /// Forget all history, e.g. after loading a different file.
+    pub fn reset(&mut self, text: &str) {
+        self.undo.clear();
+        self.redo.clear();
+        self.base = text.to_string();
+        self.editing_since = None;
+    }
+
+    /// Note that the text was just edited.
+    pub fn edited(&mut self) {
+        self.editing_since.get_or_insert_with(Instant::now);
+    }
+
+    /// Turn a finished burst of edits into an undo step; call once per frame.
+    pub fn tick(&mut self, text: &str) {
+        if self.editing_since.is_some_and(|t| t.elapsed() >= EDIT_DEBOUNCE) {
+            self.commit(text);
+        }
+    }
+
+    fn commit(&mut self, text: &str) {
+        self.editing_since = None;
+        if text == self.base {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.base, text.to_string());
+        self.undo.push(previous);
+        if self.undo.len() > MAX_DEPTH {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.editing_since.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Restore the previous snapshot into `text`. Returns false if there is none.
+    pub fn undo(&mut self, text: &mut String) -> bool {
+        // Edits still in progress count as the newest step
+        self.commit(text);
+        let Some(previous) = self.undo.pop() else {
+            return false;
+        };
+        self.redo.push(std::mem::replace(text, previous));
+        self.base.clone_from(text);
+        true
+    }
+
+    /// Re-apply the last undone snapshot. Returns false if there is none.
+    pub fn redo(&mut self, text: &mut String) -> bool {
+        // New edits since the undo clear the redo steps
+        self.commit(text);
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push(std::mem::replace(text, next));
+        self.base.clone_from(text);
+        true
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::mpsc;
+use std::thread;
+
+use eframe::egui;
+
+use crate::net;
+use crate::settings::Settings;
+
+/// Latest published release, in GitHub's release API format.
+const RELEASES_URL: &str = "https://api.github.com/repos/wangwingzero/fly-podcast/releases/latest";
+
+/// Version of this build, as shown in the bottom bar.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(serde::Deserialize)]
+struct ReleaseJson {
+    tag_name: String,
+    html_url: String,
+}
+
+/// A release newer than the running build.
+pub struct Release {
+    /// Version without the leading `v`.
+    pub version: String,
+    pub url: String,
+}
+
+/// Background check against the published releases, started on request only.
+pub struct UpdateCheck {
+    rx: Option<mpsc::Receiver<Result<Option<Release>, String>>>,
+    result: Option<Result<Option<Release>, String>>,
+}
+
+impl UpdateCheck {
+    /// Start fetching the latest release off the UI thread, through the configured proxy.
+    pub fn start(ctx: &egui::Context, settings: &Settings) -> Self {
+        let client = match net::http_client(settings) {
+            Ok(client) => client,
+            Err(e) => return Self { rx: None, result: Some(Err(e)) },
+        };
+        let (tx, rx) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let _ = tx.send(fetch_latest(&client));
+            ctx.request_repaint();
+        });
+        Self { rx: Some(rx), result: None }
+    }
+
+    /// `Some(release)` if a newer one exists, `None` if up to date; the
+    /// outer `None` while still running.
+    pub fn poll(&mut self) -> Option<&Result<Option<Release>, String>> {
+        if let Some(rx) = &self.rx {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.result = Some(result);
+                    self.rx = None;
+                }
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.result = Some(Err("检查线程意外退出".to_string()));
+                    self.rx = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
+        }
+        self.result.as_ref()
+    }
+}
+
+fn fetch_latest(client: &reqwest::blocking::Client) -> Result<Option<Release>, String> {
+    let body = client
+        .get(RELEASES_URL)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.text())
+        .map_err(|e| format!("无法获取版本信息: {e}"))?;
+    let release: ReleaseJson =
+        serde_json::from_str(&body).map_err(|e| format!("版本信息格式错误: {e}"))?;
+    let version = release.tag_name.trim_start_matches('v').to_string();
+    let newer = is_newer(&version, CURRENT_VERSION);
+    Ok(newer.then_some(Release { version, url: release.html_url }))
+}
+
+/// Compare dotted version numbers; a pre-release suffix (`-beta.1`) is ignored.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parts = |v: &str| -> Vec<u64> {
+        v.split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|n| n.trim().parse().unwrap_or(0))
+            .collect()
+    };
+    let (mut candidate, mut current) = (parts(candidate), parts(current));
+    // "1.2" and "1.2.0" are the same version
+    let len = candidate.len().max(current.len());
+    candidate.resize(len, 0);
+    current.resize(len, 0);
+    candidate > current
+}
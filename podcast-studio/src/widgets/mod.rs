@@ -1 +1,3 @@
+pub mod script_editor;
 pub mod timeline;
+pub mod toast;
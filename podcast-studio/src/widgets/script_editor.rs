@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use eframe::egui::{self, Color32, RichText};
+
+use crate::script::{Script, Turn};
+
+/// Deferred edit to a list of turns, applied after the list has been drawn.
+enum TurnAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    /// Insert an empty turn at this index.
+    Insert(usize),
+    Delete(usize),
+}
+
+/// Draw an editable view of the script. Returns true if anything changed.
+///
+/// `scroll_to` is a turn index in playback order to bring into view, e.g. after
+/// a click in the outline. `engines` labels turns, by the same index, with the
+/// TTS engine of the last audio run, and `selected` holds the turns ticked for
+/// synthesizing again.
+pub fn draw_script_editor(
+    ui: &mut egui::Ui,
+    script: &mut Script,
+    speakers: &[String],
+    scroll_to: Option<usize>,
+    engines: &BTreeMap<usize, String>,
+    selected: &mut BTreeSet<usize>,
+) -> bool {
+    let mut changed = false;
+    let mut total = script.turns().count();
+
+    ui.horizontal(|ui| {
+        ui.label("标题");
+        changed |= ui
+            .add(egui::TextEdit::singleline(&mut script.title).desired_width(f32::INFINITY))
+            .changed();
+    });
+
+    let mut first_turn = 0;
+    for (c, chapter) in script.chapters.iter_mut().enumerate() {
+        let len = chapter.dialogue.len();
+        // Target relative to this chapter, if it falls inside it
+        let target = scroll_to
+            .filter(|t| (first_turn..first_turn + len).contains(t))
+            .map(|t| t - first_turn);
+        let response = egui::CollapsingHeader::new(format!("{} ({} 句)", chapter.title, len))
+            .id_salt(("script_chapter", c))
+            .default_open(true)
+            .open(target.map(|_| true))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("章节标题");
+                    changed |= ui.text_edit_singleline(&mut chapter.title).changed();
+                });
+                // The chapter's first turn is shown by scrolling to its header
+                let turns = Turns { list_id: c + 1, first_turn, total, scroll_to: target.filter(|t| *t > 0) };
+                changed |= draw_turns(ui, &mut chapter.dialogue, speakers, turns, engines, selected);
+            });
+        if target == Some(0) {
+            response.header_response.scroll_to_me(Some(egui::Align::TOP));
+        }
+        // Turns may have been inserted or deleted above
+        let new_len = chapter.dialogue.len();
+        total = total + new_len - len;
+        first_turn += new_len;
+    }
+    if !script.dialogue.is_empty() {
+        let target = scroll_to.and_then(|t| t.checked_sub(first_turn));
+        let turns = Turns { list_id: 0, first_turn, total, scroll_to: target };
+        changed |= draw_turns(ui, &mut script.dialogue, speakers, turns, engines, selected);
+    }
+
+    changed
+}
+
+/// Where a list of turns sits in the script.
+struct Turns {
+    /// Keeps widget ids unique across chapters.
+    list_id: usize,
+    /// Playback index of the list's first turn.
+    first_turn: usize,
+    /// Number of turns in the whole script.
+    total: usize,
+    /// Index within the list to bring into view.
+    scroll_to: Option<usize>,
+}
+
+/// Draw one list of turns.
+fn draw_turns(
+    ui: &mut egui::Ui,
+    turns: &mut Vec<Turn>,
+    speakers: &[String],
+    Turns { list_id, first_turn, total, scroll_to }: Turns,
+    engines: &BTreeMap<usize, String>,
+    selected: &mut BTreeSet<usize>,
+) -> bool {
+    let mut changed = false;
+    let mut action = None;
+    let count = turns.len();
+
+    if insert_button(ui, ("insert", list_id, 0)) {
+        action = Some(TurnAction::Insert(0));
+    }
+    for (i, turn) in turns.iter_mut().enumerate() {
+        let row = ui.push_id(("turn", list_id, i), |ui| {
+            ui.horizontal(|ui| {
+                let index = first_turn + i;
+                let mut checked = selected.contains(&index);
+                if ui.checkbox(&mut checked, "").on_hover_text("选中后可在生成音频时只重新合成这些句子").changed() {
+                    if checked {
+                        selected.insert(index);
+                    } else {
+                        selected.remove(&index);
+                    }
+                }
+                ui.label(RichText::new(format!("{:>3}", i + 1)).monospace().color(Color32::from_rgb(156, 163, 175)));
+
+                ui.vertical(|ui| {
+                    if ui.add_enabled(i > 0, egui::Button::new("↑").small()).on_hover_text("上移").clicked() {
+                        action = Some(TurnAction::MoveUp(i));
+                    }
+                    if ui.add_enabled(i + 1 < count, egui::Button::new("↓").small()).on_hover_text("下移").clicked() {
+                        action = Some(TurnAction::MoveDown(i));
+                    }
+                });
+
+                egui::ComboBox::from_id_salt("role")
+                    .selected_text(&turn.role)
+                    .width(80.0)
+                    .show_ui(ui, |ui| {
+                        for speaker in speakers {
+                            if ui.selectable_label(turn.role == *speaker, speaker).clicked() && turn.role != *speaker {
+                                turn.role = speaker.clone();
+                                changed = true;
+                            }
+                        }
+                    });
+
+                if let Some(engine) = engines.get(&(first_turn + i)) {
+                    engine_badge(ui, engine);
+                }
+
+                // Keep at least one turn so the list never disappears
+                let delete = ui
+                    .add_enabled(count > 1, egui::Button::new("🗑").small())
+                    .on_hover_text("删除")
+                    .on_disabled_hover_text("至少保留一句");
+                if delete.clicked() {
+                    action = Some(TurnAction::Delete(i));
+                }
+
+                changed |= ui
+                    .add(
+                        egui::TextEdit::multiline(&mut turn.text)
+                            .desired_rows(1)
+                            .desired_width(f32::INFINITY),
+                    )
+                    .changed();
+            });
+        });
+        if scroll_to == Some(i) {
+            row.response.scroll_to_me(Some(egui::Align::TOP));
+        }
+        if insert_button(ui, ("insert", list_id, i + 1)) {
+            action = Some(TurnAction::Insert(i + 1));
+        }
+    }
+
+    // Segment audio is stored by position, so every turn that moves is
+    // selected for synthesizing again
+    match action {
+        Some(TurnAction::MoveUp(i)) => {
+            turns.swap(i - 1, i);
+            selected.extend([first_turn + i - 1, first_turn + i]);
+            changed = true;
+        }
+        Some(TurnAction::MoveDown(i)) => {
+            turns.swap(i, i + 1);
+            selected.extend([first_turn + i, first_turn + i + 1]);
+            changed = true;
+        }
+        Some(TurnAction::Insert(i)) => {
+            // Alternate with the turn before, or the one after when inserting first
+            let neighbour = if i > 0 { turns.get(i - 1) } else { turns.first() };
+            let role = neighbour.map_or_else(
+                || speakers.first().cloned().unwrap_or_default(),
+                |turn| counterpart(&turn.role, speakers),
+            );
+            turns.insert(i, Turn { role, ..Default::default() });
+            selected.extend(first_turn + i..=total);
+            changed = true;
+        }
+        Some(TurnAction::Delete(i)) if turns.len() > 1 => {
+            turns.remove(i);
+            selected.retain(|&t| t + 1 < total);
+            selected.extend(first_turn + i..total - 1);
+            changed = true;
+        }
+        Some(TurnAction::Delete(_)) | None => {}
+    }
+    changed
+}
+
+/// Small tag naming the TTS engine that voiced a turn.
+fn engine_badge(ui: &mut egui::Ui, engine: &str) {
+    let color = match engine {
+        "DashScope" => Color32::from_rgb(234, 88, 12), // orange: paid
+        "Edge" => Color32::from_rgb(59, 130, 246),     // blue: fallback voice
+        "Qwen" => Color32::from_rgb(34, 197, 94),      // green
+        _ => Color32::from_rgb(156, 163, 175),         // gray: mixed or unknown
+    };
+    egui::Frame::new()
+        .stroke(egui::Stroke::new(1.0, color))
+        .corner_radius(3.0)
+        .inner_margin(egui::Margin::symmetric(3, 0))
+        .show(ui, |ui| ui.label(RichText::new(engine).small().color(color)))
+        .response
+        .on_hover_text("上次生成音频时使用的 TTS 引擎");
+}
+
+/// A small "+" between turns; returns true when clicked.
+fn insert_button(ui: &mut egui::Ui, id: impl std::hash::Hash) -> bool {
+    ui.push_id(id, |ui| {
+        ui.add(egui::Button::new(RichText::new("+").color(Color32::from_rgb(156, 163, 175))).small().frame(false))
+            .on_hover_text("在此插入一句")
+            .clicked()
+    })
+    .inner
+}
+
+/// The other speaker in a dialogue: the first known speaker that isn't `role`.
+fn counterpart(role: &str, speakers: &[String]) -> String {
+    speakers
+        .iter()
+        .find(|s| *s != role)
+        .cloned()
+        .unwrap_or_else(|| role.to_string())
+}
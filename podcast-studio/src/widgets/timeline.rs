@@ -2,20 +2,38 @@ use eframe::egui::{self, Color32, Pos2, Rect, Stroke, Vec2};
 
 use crate::pipeline::{StepStatus, STEPS};
 
+// Sizes in points at egui's default body text size; `Metrics` scales them
+// with the font size setting. Zoom and display scaling apply on top in egui.
+const BASE_FONT_SIZE: f32 = 12.5;
 const CIRCLE_RADIUS: f32 = 14.0;
 const LINE_WIDTH: f32 = 3.0;
-const STEP_SPACING: f32 = 90.0;
+/// Vertical distance between step centers, shrunk to fit more steps.
+const MAX_STEP_SPACING: f32 = 90.0;
+const MIN_STEP_SPACING: f32 = 56.0;
+/// Space above the first and below the last step center.
+const START_Y: f32 = 40.0;
+const END_PADDING: f32 = 40.0;
+/// Half the height of the current-step highlight.
+const ROW_HALF_HEIGHT: f32 = 22.0;
+/// Circle center from the left edge, unless centered in compact mode.
+const LEFT_X: f32 = 40.0;
+const LABEL_GAP: f32 = 12.0;
+const ICON_FONT_SIZE: f32 = 12.0;
+const LABEL_FONT_SIZE: f32 = 14.0;
 
 const COLOR_DONE: Color32 = Color32::from_rgb(34, 197, 94);     // green
 const COLOR_RUNNING: Color32 = Color32::from_rgb(59, 130, 246); // blue
+const COLOR_QUEUED: Color32 = Color32::from_rgb(139, 92, 246);  // violet
 const COLOR_FAILED: Color32 = Color32::from_rgb(239, 68, 68);   // red
 const COLOR_PENDING: Color32 = Color32::from_rgb(156, 163, 175); // gray
 const COLOR_CURRENT_BG: Color32 = Color32::from_rgb(239, 246, 255); // light blue bg
+const COLOR_KEYBOARD: Color32 = Color32::from_rgb(245, 158, 11); // amber outline
 
 fn status_color(status: &StepStatus) -> Color32 {
     match status {
         StepStatus::Done => COLOR_DONE,
         StepStatus::Running => COLOR_RUNNING,
+        StepStatus::Queued => COLOR_QUEUED,
         StepStatus::Failed(_) => COLOR_FAILED,
         StepStatus::Pending => COLOR_PENDING,
     }
@@ -25,138 +43,294 @@ fn status_icon(status: &StepStatus) -> &'static str {
     match status {
         StepStatus::Done => "\u{2714}",    // check mark
         StepStatus::Running => "\u{23F3}", // hourglass
+        StepStatus::Queued => "\u{2026}",  // ellipsis
         StepStatus::Failed(_) => "\u{2716}", // X mark
         StepStatus::Pending => "",
     }
 }
 
-/// Draw the vertical timeline on the left panel. Returns the index of clicked step (if any).
+/// How much larger than the defaults the style's body text is.
+pub fn font_scale(style: &egui::Style) -> f32 {
+    style
+        .text_styles
+        .get(&egui::TextStyle::Body)
+        .map_or(1.0, |font| font.size / BASE_FONT_SIZE)
+}
+
+/// Timeline geometry for the current style.
+#[derive(Clone, Copy)]
+struct Metrics {
+    scale: f32,
+    spacing: f32,
+}
+
+impl Metrics {
+    fn new(style: &egui::Style) -> Self {
+        let scale = font_scale(style);
+        Self { scale, spacing: MAX_STEP_SPACING * scale }
+    }
+
+    /// Spacing that fits `count` steps into `available` height, within sane bounds.
+    fn fit(mut self, available: f32, count: usize) -> Self {
+        if count >= 2 {
+            let fitted = (available - self.start_y() - self.end_padding()) / (count - 1) as f32;
+            self.spacing = fitted.clamp(MIN_STEP_SPACING * self.scale, MAX_STEP_SPACING * self.scale);
+        }
+        self
+    }
+
+    fn circle_radius(self) -> f32 {
+        CIRCLE_RADIUS * self.scale
+    }
+
+    fn start_y(self) -> f32 {
+        START_Y * self.scale
+    }
+
+    fn end_padding(self) -> f32 {
+        END_PADDING * self.scale
+    }
+
+    fn row_half_height(self) -> f32 {
+        ROW_HALF_HEIGHT * self.scale
+    }
+
+    /// Center of step `i`, from the top of the timeline.
+    fn center_y(self, i: usize) -> f32 {
+        self.start_y() + i as f32 * self.spacing
+    }
+
+    fn total_height(self, count: usize) -> f32 {
+        self.center_y(count.saturating_sub(1)) + self.end_padding()
+    }
+
+    /// Step whose row contains `y` (measured from the top of the timeline).
+    ///
+    /// Rows are half-open bands `[center - spacing/2, center + spacing/2)`, so
+    /// adjacent rows never overlap. The first row starts at the top, and the last
+    /// ends just below its highlight, so the empty space further down selects nothing.
+    fn step_at(self, y: f32, count: usize) -> Option<usize> {
+        if count == 0 || y < 0.0 {
+            return None;
+        }
+        if y >= self.center_y(count - 1) + self.row_half_height() {
+            return None;
+        }
+        let first_bottom = self.start_y() + self.spacing / 2.0;
+        if y < first_bottom {
+            return Some(0);
+        }
+        let i = 1 + ((y - first_bottom) / self.spacing).floor() as usize;
+        Some(i.min(count - 1))
+    }
+}
+
+/// Draw the vertical timeline on the left panel. Returns the index of the step
+/// clicked, or chosen with Enter (if any).
+///
+/// The timeline takes keyboard focus (Tab, or a click); Up/Down then move an
+/// outlined selection, separate from the current step's highlight.
+///
+/// Scrolls when the steps don't fit even at the minimum spacing. `compact`
+/// draws only the circles, centered, with the step names moved to the tooltip.
 pub fn draw_timeline(
     ui: &mut egui::Ui,
-    steps: &[StepStatus; 5],
+    steps: &[StepStatus],
     current_step: usize,
+    compact: bool,
 ) -> Option<usize> {
-    let start_y = 40.0;
-    let left_x = 40.0;
-    let panel_rect = ui.available_rect_before_wrap();
-    let base_y = panel_rect.min.y; // Y offset from panel top (below heading/separator)
-
-    // Collect label rects for click handling (computed during paint)
-    let mut label_rects: [(Rect, bool); 5] = [(Rect::NOTHING, false); 5];
-
-    // Paint everything first
-    {
-        let painter = ui.painter();
-
-        // Draw connecting lines
-        for i in 0..4 {
-            let y1 = base_y + start_y + i as f32 * STEP_SPACING + CIRCLE_RADIUS;
-            let y2 = base_y + start_y + (i + 1) as f32 * STEP_SPACING - CIRCLE_RADIUS;
-            let color = if steps[i] == StepStatus::Done {
-                COLOR_DONE
-            } else {
-                COLOR_PENDING.linear_multiply(0.5)
-            };
-            painter.line_segment(
-                [
-                    Pos2::new(panel_rect.min.x + left_x, y1),
-                    Pos2::new(panel_rect.min.x + left_x, y2),
-                ],
-                Stroke::new(LINE_WIDTH, color),
-            );
-        }
+    let metrics = Metrics::new(ui.style()).fit(ui.available_height(), steps.len());
+    egui::ScrollArea::vertical()
+        .id_salt("timeline")
+        .auto_shrink([false, true])
+        .show(ui, |ui| paint_timeline(ui, steps, current_step, metrics, compact))
+        .inner
+}
 
-        // Draw circles and labels
-        for (i, step_info) in STEPS.iter().enumerate() {
-            let center_y = base_y + start_y + i as f32 * STEP_SPACING;
-            let center = Pos2::new(panel_rect.min.x + left_x, center_y);
-            let color = status_color(&steps[i]);
-
-            // Highlight background for current step
-            if i == current_step {
-                let highlight_rect = Rect::from_min_size(
-                    Pos2::new(panel_rect.min.x + 4.0, center_y - 22.0),
-                    Vec2::new(panel_rect.width() - 8.0, 44.0),
-                );
-                painter.rect_filled(highlight_rect, 6.0, COLOR_CURRENT_BG);
-            }
+fn paint_timeline(
+    ui: &mut egui::Ui,
+    steps: &[StepStatus],
+    current_step: usize,
+    metrics: Metrics,
+    compact: bool,
+) -> Option<usize> {
+    // Reserve the space first; painting and hit-testing both derive from this
+    // one rect, which also moves with the scroll offset
+    let total_height = metrics.total_height(steps.len());
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), total_height), egui::Sense::hover());
+    // A fixed id keeps the keyboard selection across frames
+    let id = ui.make_persistent_id("timeline_steps");
+    let response = ui.interact(rect, id, egui::Sense::click());
+    // One response for the whole timeline; `step_at` maps each point to at most one step
+    let row_at = |pos: Option<Pos2>| pos.and_then(|p| metrics.step_at(p.y - rect.min.y, steps.len()));
+    let hovered = row_at(response.hover_pos());
+    let mut clicked = if response.clicked() { row_at(response.interact_pointer_pos()) } else { None };
+    if clicked.is_some() {
+        response.request_focus();
+    }
+    let keyboard = keyboard_selection(ui, &response, steps.len(), current_step, clicked);
+    if let Some(KeyboardSelection { selected, entered: true, .. }) = keyboard {
+        clicked = Some(selected);
+    }
 
-            // Circle
-            if steps[i] == StepStatus::Done {
-                painter.circle_filled(center, CIRCLE_RADIUS, color);
-            } else {
-                painter.circle_stroke(center, CIRCLE_RADIUS, Stroke::new(2.5, color));
-            }
+    let scale = metrics.scale;
+    let radius = metrics.circle_radius();
+    let left_x = if compact { rect.width() / 2.0 } else { LEFT_X * scale };
+    let painter = ui.painter_at(rect);
+    let row_rect = |center_y: f32| {
+        Rect::from_min_size(
+            Pos2::new(rect.min.x + 4.0, center_y - metrics.row_half_height()),
+            Vec2::new(rect.width() - 8.0, metrics.row_half_height() * 2.0),
+        )
+    };
 
-            // Icon inside circle
-            let icon = status_icon(&steps[i]);
-            if !icon.is_empty() {
-                let icon_color = if steps[i] == StepStatus::Done {
-                    Color32::WHITE
-                } else {
-                    color
-                };
-                painter.text(
-                    center,
-                    egui::Align2::CENTER_CENTER,
-                    icon,
-                    egui::FontId::proportional(12.0),
-                    icon_color,
-                );
-            } else {
-                painter.text(
-                    center,
-                    egui::Align2::CENTER_CENTER,
-                    format!("{}", i + 1),
-                    egui::FontId::proportional(12.0),
-                    color,
-                );
+    // Draw connecting lines
+    for (i, status) in steps.iter().take(steps.len().saturating_sub(1)).enumerate() {
+        let y1 = rect.min.y + metrics.center_y(i) + radius;
+        let y2 = rect.min.y + metrics.center_y(i + 1) - radius;
+        let color = if *status == StepStatus::Done {
+            COLOR_DONE
+        } else {
+            COLOR_PENDING.linear_multiply(0.5)
+        };
+        painter.line_segment(
+            [
+                Pos2::new(rect.min.x + left_x, y1),
+                Pos2::new(rect.min.x + left_x, y2),
+            ],
+            Stroke::new(LINE_WIDTH * scale, color),
+        );
+    }
+
+    // Draw circles and labels
+    for (i, step_info) in STEPS.iter().enumerate().take(steps.len()) {
+        let center_y = rect.min.y + metrics.center_y(i);
+        let center = Pos2::new(rect.min.x + left_x, center_y);
+        let color = status_color(&steps[i]);
+
+        // Highlight background for current step, fainter for the hovered one
+        if i == current_step {
+            painter.rect_filled(row_rect(center_y), 6.0, COLOR_CURRENT_BG);
+        } else if hovered == Some(i) {
+            painter.rect_filled(row_rect(center_y), 6.0, COLOR_CURRENT_BG.linear_multiply(0.5));
+        }
+        if let Some(keyboard) = keyboard.filter(|k| k.selected == i) {
+            painter.rect_stroke(row_rect(center_y), 6.0, Stroke::new(2.0, COLOR_KEYBOARD), egui::StrokeKind::Inside);
+            if keyboard.moved {
+                ui.scroll_to_rect(row_rect(center_y), None);
             }
+        }
+
+        // Circle
+        if steps[i] == StepStatus::Done {
+            painter.circle_filled(center, radius, color);
+        } else {
+            painter.circle_stroke(center, radius, Stroke::new(2.5 * scale, color));
+        }
 
-            // Step label
-            let label_pos = Pos2::new(center.x + CIRCLE_RADIUS + 12.0, center_y);
-            let text_color = if i == current_step {
-                Color32::from_rgb(30, 58, 138)
+        // Icon inside circle
+        let icon = status_icon(&steps[i]);
+        if !icon.is_empty() {
+            let icon_color = if steps[i] == StepStatus::Done {
+                Color32::WHITE
             } else {
-                Color32::from_rgb(75, 85, 99)
+                color
             };
-
-            let label_rect = painter.text(
-                label_pos,
-                egui::Align2::LEFT_CENTER,
-                step_info.name,
-                egui::FontId::proportional(14.0),
-                text_color,
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                icon,
+                egui::FontId::proportional(ICON_FONT_SIZE * scale),
+                icon_color,
             );
-
-            // Full row clickable area (circle + label + padding)
-            let row_rect = Rect::from_min_size(
-                Pos2::new(panel_rect.min.x, center_y - STEP_SPACING / 2.0),
-                Vec2::new(panel_rect.width(), STEP_SPACING),
+        } else {
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                format!("{}", i + 1),
+                egui::FontId::proportional(ICON_FONT_SIZE * scale),
+                color,
             );
-            label_rects[i] = (row_rect, true);
         }
-    }
-    // painter borrow released here
 
-    // Reserve space first, then handle clicks
-    let total_height = start_y + 4.0 * STEP_SPACING + 40.0;
-    ui.allocate_space(Vec2::new(panel_rect.width(), total_height));
+        if compact {
+            continue;
+        }
 
-    // Handle clicks (separate pass, no painter borrow)
-    let mut clicked = None;
-    for (i, (rect, clickable)) in label_rects.iter().enumerate() {
-        if *clickable {
-            let response = ui.allocate_rect(*rect, egui::Sense::click());
-            if response.clicked() {
-                clicked = Some(i);
-            }
-            // Hover cursor hint
-            if response.hovered() {
-                ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
-            }
+        // Step label
+        let label_pos = Pos2::new(center.x + radius + LABEL_GAP * scale, center_y);
+        let text_color = if i == current_step {
+            Color32::from_rgb(30, 58, 138)
+        } else {
+            Color32::from_rgb(75, 85, 99)
+        };
+
+        painter.text(
+            label_pos,
+            egui::Align2::LEFT_CENTER,
+            step_info.name,
+            egui::FontId::proportional(LABEL_FONT_SIZE * scale),
+            text_color,
+        );
+    }
+
+    // Hover cursor hint, plus what the step does
+    if let Some(i) = hovered {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+        if compact {
+            response.on_hover_text(format!("{}\n{}", STEPS[i].name, STEPS[i].description));
+        } else {
+            response.on_hover_text(STEPS[i].description);
         }
     }
 
     clicked
 }
+
+#[derive(Clone, Copy)]
+struct KeyboardSelection {
+    selected: usize,
+    /// Enter or Space was pressed this frame.
+    entered: bool,
+    /// An arrow key moved the selection this frame.
+    moved: bool,
+}
+
+/// Keyboard selection while the timeline has focus.
+///
+/// Starts at the current step (or the clicked one) and is kept in egui's
+/// memory under the timeline's id.
+fn keyboard_selection(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    count: usize,
+    current_step: usize,
+    clicked: Option<usize>,
+) -> Option<KeyboardSelection> {
+    let id = response.id;
+    if !response.has_focus() || count == 0 {
+        ui.data_mut(|d| d.remove::<usize>(id));
+        return None;
+    }
+    // Arrows would otherwise move focus to the next widget
+    ui.memory_mut(|m| {
+        m.set_focus_lock_filter(id, egui::EventFilter { vertical_arrows: true, ..Default::default() });
+    });
+    let previous = ui.data(|d| d.get_temp::<usize>(id));
+    let mut selected = clicked.or(previous).unwrap_or(current_step).min(count - 1);
+    let (up, down, entered) = ui.input(|i| {
+        (
+            i.key_pressed(egui::Key::ArrowUp),
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter) || i.key_pressed(egui::Key::Space),
+        )
+    });
+    if up {
+        selected = selected.saturating_sub(1);
+    }
+    if down {
+        selected = (selected + 1).min(count - 1);
+    }
+    ui.data_mut(|d| d.insert_temp(id, selected));
+    Some(KeyboardSelection { selected, entered, moved: up || down })
+}
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+use eframe::egui::{self, Color32, RichText};
+
+const SUCCESS_DURATION: Duration = Duration::from_millis(2500);
+/// Errors stay up longer so they can actually be read.
+const ERROR_DURATION: Duration = Duration::from_secs(6);
+/// Oldest toasts are dropped beyond this many.
+const MAX_TOASTS: usize = 4;
+/// Keeps toasts clear of the bottom navigation bar.
+const BOTTOM_OFFSET: f32 = 48.0;
+
+const COLOR_SUCCESS: Color32 = Color32::from_rgb(34, 197, 94); // green
+const COLOR_ERROR: Color32 = Color32::from_rgb(239, 68, 68);   // red
+
+struct Toast {
+    text: String,
+    is_error: bool,
+    expires_at: Instant,
+}
+
+/// Short-lived messages shown in the bottom-right corner.
+#[derive(Default)]
+pub struct Toasts {
+    items: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn success(&mut self, text: impl Into<String>) {
+        self.push(text.into(), false, SUCCESS_DURATION);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(text.into(), true, ERROR_DURATION);
+    }
+
+    fn push(&mut self, text: String, is_error: bool, duration: Duration) {
+        // Repeating the newest message just extends it
+        if let Some(last) = self.items.last_mut() {
+            if last.text == text && last.is_error == is_error {
+                last.expires_at = Instant::now() + duration;
+                return;
+            }
+        }
+        self.items.push(Toast { text, is_error, expires_at: Instant::now() + duration });
+        if self.items.len() > MAX_TOASTS {
+            self.items.remove(0);
+        }
+    }
+
+    /// Draw the live toasts over everything else; a click dismisses one.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        self.items.retain(|t| t.expires_at > now);
+        let Some(next_expiry) = self.items.iter().map(|t| t.expires_at).min() else {
+            return;
+        };
+        ctx.request_repaint_after(next_expiry - now);
+
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -BOTTOM_OFFSET))
+            .show(ctx, |ui| {
+                for (i, toast) in self.items.iter().enumerate() {
+                    let color = if toast.is_error { COLOR_ERROR } else { COLOR_SUCCESS };
+                    let icon = if toast.is_error { "\u{2716}" } else { "\u{2714}" };
+                    let response = egui::Frame::popup(ui.style())
+                        .stroke(egui::Stroke::new(1.0, color))
+                        .show(ui, |ui| {
+                            ui.set_max_width(360.0);
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(icon).color(color));
+                                ui.label(&toast.text);
+                            });
+                        })
+                        .response
+                        .interact(egui::Sense::click());
+                    if response.on_hover_text("点击关闭").clicked() {
+                        dismissed = Some(i);
+                    }
+                }
+            });
+        if let Some(i) = dismissed {
+            self.items.remove(i);
+        }
+    }
+}
@@ -25,7 +25,7 @@ from flying_podcast.stages.podcast import run_script as podcast_script
 from flying_podcast.stages.podcast import run_audio as podcast_audio
 from flying_podcast.stages.podcast_inbox import run as podcast_inbox
 from flying_podcast.stages.publish import run as publish
-from flying_podcast.stages.publish_podcast import run as publish_podcast
+from flying_podcast.stages.publish_podcast import PublishError, run as publish_podcast
 from flying_podcast.stages.rank import run as rank
 from flying_podcast.stages.verify import run as verify
 
@@ -56,13 +56,32 @@ def main() -> None:
     parser.add_argument("--local-only", dest="local_only", action="store_true",
                         help="Only process PDFs in inbox/pending/ (for podcast-inbox)")
     parser.add_argument("--dry-run", dest="dry_run_flag", action="store_true",
-                        help="Show what would be processed without generating (for podcast-inbox)")
+                        help="Show what would be processed without generating (for podcast-inbox), "
+                             "or write publish_preview.json without uploading (for publish-podcast)")
     parser.add_argument("--podcast-dir", dest="podcast_dir", default=None,
                         help="Specific podcast output dir (for publish-podcast)")
+    parser.add_argument("--upload-audio", dest="upload_audio", action="store_true",
+                        help="Upload the audio and cover again instead of reusing earlier uploads "
+                             "(for publish-podcast)")
+    parser.add_argument("--series", dest="series", default=None,
+                        help="Series id from series.json to publish into (for publish-podcast)")
+    parser.add_argument("--episode", dest="episode", type=int, default=None,
+                        help="Episode number within --series (for publish-podcast)")
     parser.add_argument("--dir", dest="work_dir", default=None,
                         help="Work directory (for podcast-audio)")
+    parser.add_argument("--format", dest="audio_format", default="mp3", choices=["mp3", "aac", "ogg"],
+                        help="Output audio format (for podcast-audio)")
+    parser.add_argument("--segments", dest="segments", default=None,
+                        help="Comma-separated dialogue line indices (0-based) to synthesize again, "
+                             "e.g. 3,7,12 (for podcast-audio)")
     parser.add_argument("--output-dir", dest="output_dir", default=None,
                         help="Output base directory (for podcast-script)")
+    parser.add_argument("--name-template", dest="name_template", default=None,
+                        help="Work dir name template, e.g. {date}_{stem}_{time} (for podcast-script)")
+    parser.add_argument("--model", dest="model", default=None,
+                        help="LLM model, overriding LLM_MODEL (for podcast-script)")
+    parser.add_argument("--base-url", dest="base_url", default=None,
+                        help="LLM API URL, overriding LLM_BASE_URL (for podcast-script)")
     parser.add_argument("--briefing-file", dest="briefing_file", default=None,
                         help="Text file with producer LLM briefing (for podcast / podcast-script)")
     parser.add_argument("--json", dest="json_output", action="store_true",
@@ -94,13 +113,22 @@ def main() -> None:
             pdf_path=args.pdf,
             output_dir=args.output_dir,
             briefing_file=args.briefing_file,
+            name_template=args.name_template,
+            model=args.model,
+            base_url=args.base_url,
         )
         return
 
     if args.stage == "podcast-audio":
         if not args.work_dir:
             parser.error("podcast-audio requires --dir <work_directory>")
-        podcast_audio(work_dir=args.work_dir)
+        segments = None
+        if args.segments:
+            try:
+                segments = [int(s) for s in args.segments.split(",") if s.strip()]
+            except ValueError:
+                parser.error(f"--segments expects comma-separated numbers, got {args.segments!r}")
+        podcast_audio(work_dir=args.work_dir, audio_format=args.audio_format, segments=segments)
         return
 
     if args.stage == "podcast-inbox":
@@ -108,7 +136,15 @@ def main() -> None:
         return
 
     if args.stage == "publish-podcast":
-        publish_podcast(args.date, podcast_dir=args.podcast_dir)
+        if args.series and args.episode is None:
+            parser.error("--series requires --episode <n>")
+        try:
+            publish_podcast(args.date, podcast_dir=args.podcast_dir, dry_run=args.dry_run_flag,
+                            upload_audio=args.upload_audio, series=args.series, episode=args.episode)
+        except PublishError as exc:
+            # Exit non-zero so the studio and scripts don't report a draft that wasn't created
+            logger.error("Publish failed: %s", exc)
+            raise SystemExit(1) from exc
         return
 
     STAGES[args.stage](args.date)
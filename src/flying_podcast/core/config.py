@@ -112,6 +112,9 @@ class Settings:
     # Optional: qwen_local | qwen_cloud | edge | dashscope — skip fallback chain
     tts_force_backend: str = os.getenv("TTS_FORCE_BACKEND", "").strip().lower()
 
+    # Podcast work dir name; placeholders {date}, {stem} (PDF name), {time} (HHMMSS)
+    workdir_template: str = os.getenv("WORKDIR_TEMPLATE", "").strip() or "{date}_{stem}"
+
     # Podcast extra prompt (e.g. holiday greetings)
     podcast_greeting: str = os.getenv("PODCAST_GREETING", "")
 
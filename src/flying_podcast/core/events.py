@@ -0,0 +1,35 @@
+"""Structured progress events for the podcast-studio desktop app.
+
+Each event is one JSON object per line on stdout, next to the normal log
+output. The schema is defined by ``EVENT_SCHEMA`` in
+``podcast-studio/src/runner.rs``; keep the two in sync.
+"""
+from __future__ import annotations
+
+import json
+import sys
+from pathlib import Path
+
+
+def emit(event: str, **fields) -> None:
+    """Write one event line; fields must be JSON-serializable (paths are converted)."""
+    payload = {"event": event}
+    payload.update({k: str(v) if isinstance(v, Path) else v for k, v in fields.items()})
+    sys.stdout.write(json.dumps(payload, ensure_ascii=False) + "\n")
+    sys.stdout.flush()
+
+
+def progress(pct: float) -> None:
+    emit("progress", pct=round(max(0.0, min(1.0, pct)), 3))
+
+
+def phase(name: str) -> None:
+    emit("phase", name=name)
+
+
+def segment(done: int, total: int) -> None:
+    emit("segment", done=done, total=total)
+
+
+def work_dir(path: Path) -> None:
+    emit("work_dir", path=path)
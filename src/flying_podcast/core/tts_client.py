@@ -1,6 +1,7 @@
 from __future__ import annotations
 
 import asyncio
+import json
 import subprocess
 import time
 from pathlib import Path
@@ -10,6 +11,7 @@ import requests
 
 import dashscope
 
+from flying_podcast.core import events
 from flying_podcast.core.config import settings
 from flying_podcast.core.logging_utils import get_logger
 
@@ -57,6 +59,35 @@ def _qwen_role_voice_map(*, local: bool) -> dict[str, str]:
         "虎机长": male,
     }
 
+VOICE_MAPPING_FILE = "voices.json"
+
+
+def load_voice_mapping(work_dir: Path) -> dict[str, str]:
+    """Speaker → voice picked per episode in podcast-studio, from work_dir/voices.json.
+
+    The voices are Cherry/Ethan-family names, so they apply to DashScope and the
+    Qwen cloud speakers; Edge and the local 0.6B model keep their own voices.
+    """
+    path = work_dir / VOICE_MAPPING_FILE
+    try:
+        data = json.loads(path.read_text(encoding="utf-8"))
+    except (OSError, ValueError):
+        return {}
+    if not isinstance(data, dict):
+        return {}
+    return {str(k): str(v) for k, v in data.items() if isinstance(v, str) and v.strip()}
+
+
+def _speaker_preset(role: str, voices: dict[str, str]) -> dict[str, str]:
+    """DashScope voice + instructions for a speaker, with its voices.json pick applied."""
+    presets = _dashscope_voice_map()
+    preset = presets.get(role)
+    if not preset:
+        preset = presets["女"]
+    if role in voices:
+        preset = {**preset, "voice": voices[role]}
+    return preset
+
 # Edge TTS voice mapping
 EDGE_VOICE_MAP = {
     "女": "zh-CN-XiaoxiaoNeural",
@@ -192,7 +223,15 @@ def _synthesize_via_qwen_local(text: str, role: str) -> bytes:
     )
 
 
-def _synthesize_via_qwen_cloud(text: str, role: str) -> bytes:
+def _qwen_cloud_voice_map(role: str, voice: str | None) -> dict[str, str]:
+    voice_map = _qwen_role_voice_map(local=False)
+    if voice:
+        # Cloud speaker names are lower-case (cherry, ethan, ...)
+        voice_map[role] = voice.lower()
+    return voice_map
+
+
+def _synthesize_via_qwen_cloud(text: str, role: str, voice: str | None = None) -> bytes:
     """US fallback URL only (cloud/demo speaker names)."""
     if not settings.qwen_tts_fallback_url.strip():
         raise TTSError("QWEN_TTS_FALLBACK_URL is not set")
@@ -201,19 +240,21 @@ def _synthesize_via_qwen_cloud(text: str, role: str) -> bytes:
         role,
         settings.qwen_tts_fallback_url,
         label="qwen-cloud",
-        voice_map=_qwen_role_voice_map(local=False),
+        voice_map=_qwen_cloud_voice_map(role, voice),
         use_local_model=False,
         timeout=300,
     )
 
 
-def _synthesize_via_qwen_api(text: str, role: str) -> bytes:
+def _synthesize_via_qwen_api(text: str, role: str, cloud_voice: str | None = None) -> bytes:
     """Qwen TTS: HK 0.6B and US tts2api, ordered by QWEN_TTS_PREFER_CLOUD_VOICES."""
     endpoints = _qwen_speech_endpoints()
     errors: list[str] = []
     for label, base_url, voice_map, use_local, timeout in endpoints:
         if not base_url.strip():
             continue
+        if not use_local:
+            voice_map = _qwen_cloud_voice_map(role, cloud_voice)
         try:
             mp3 = _synthesize_qwen_at_url(
                 text,
@@ -307,16 +348,21 @@ def _synthesize_one(
     role: str,
     backend: str,
     retries: int = 2,
+    speaker_voice: str | None = None,
 ) -> bytes:
-    """Synthesize a single segment using exactly ONE backend (with retries)."""
+    """Synthesize a single segment using exactly ONE backend (with retries).
+
+    ``speaker_voice`` is the voices.json pick for the Qwen cloud speakers;
+    DashScope already gets it through ``voice``.
+    """
     for attempt in range(1, retries + 1):
         try:
             if backend == "qwen_api":
-                return _synthesize_via_qwen_api(text, role)
+                return _synthesize_via_qwen_api(text, role, cloud_voice=speaker_voice)
             elif backend == "qwen_local":
                 return _synthesize_via_qwen_local(text, role)
             elif backend == "qwen_cloud":
-                return _synthesize_via_qwen_cloud(text, role)
+                return _synthesize_via_qwen_cloud(text, role, voice=speaker_voice)
             elif backend == "edge":
                 return _synthesize_via_edge_tts(text, role)
             elif backend == "dashscope":
@@ -374,11 +420,30 @@ def synthesize_segment(
 #   4. Last resort → clean slate, DashScope for all (paid)
 
 
+SEGMENT_ENGINES_FILE = "engines.json"
+
+
+def _load_segment_engines(output_dir: Path) -> dict[str, str]:
+    """Backend that produced each segment file, as recorded in engines.json."""
+    path = output_dir / SEGMENT_ENGINES_FILE
+    try:
+        data = json.loads(path.read_text(encoding="utf-8"))
+    except (OSError, ValueError):
+        return {}
+    return data if isinstance(data, dict) else {}
+
+
+def _save_segment_engines(output_dir: Path, engines: dict[str, str]) -> None:
+    path = output_dir / SEGMENT_ENGINES_FILE
+    path.write_text(json.dumps(engines, ensure_ascii=False, indent=2, sort_keys=True), encoding="utf-8")
+
+
 def _try_all_segments(
     dialogue: list[dict[str, str]],
     output_dir: Path,
     backend: str,
     retries: int = 2,
+    voices: dict[str, str] | None = None,
 ) -> tuple[list[Path | None], list[dict]]:
     """Try all segments with one backend, continuing past failures.
 
@@ -386,14 +451,17 @@ def _try_all_segments(
     """
     files: list[Path | None] = []
     failed: list[dict] = []
+    # Segments kept from an earlier run keep the backend recorded then
+    engines = _load_segment_engines(output_dir)
+    voices = voices or {}
+    total = sum(len(_split_text(line["text"], MAX_CHARS_PER_REQUEST)) for line in dialogue)
 
     for i, line in enumerate(dialogue):
         role = line["role"]
         text = line["text"]
-        preset = _dashscope_voice_map().get(role)
-        if not preset:
+        if role not in _dashscope_voice_map() and role not in voices:
             logger.warning("Unknown role '%s' at line %d, defaulting to female", role, i)
-            preset = _dashscope_voice_map()["女"]
+        preset = _speaker_preset(role, voices)
 
         chunks = _split_text(text, MAX_CHARS_PER_REQUEST)
 
@@ -405,6 +473,7 @@ def _try_all_segments(
             if seg_path.exists():
                 logger.debug("Segment already exists: %s", seg_path.name)
                 files.append(seg_path)
+                events.segment(len(files), total)
                 continue
 
             logger.info("TTS [%s][%s] seg %d%s: %s...", backend, role, i, suffix, chunk[:30])
@@ -412,18 +481,22 @@ def _try_all_segments(
                 audio_bytes = _synthesize_one(
                     chunk, preset["voice"], preset["instructions"],
                     role=role, backend=backend, retries=retries,
+                    speaker_voice=voices.get(role),
                 )
                 with open(seg_path, "wb") as f:
                     f.write(audio_bytes)
                 files.append(seg_path)
+                engines[seg_path.name] = backend
+                _save_segment_engines(output_dir, engines)
             except TTSError as exc:
                 logger.warning("[TTS] %s failed seg %d%s: %s", backend, i, suffix, exc)
                 files.append(None)
                 failed.append({
                     "idx": idx, "seg_path": seg_path, "chunk": chunk,
-                    "preset": preset, "role": role,
+                    "preset": preset, "role": role, "speaker_voice": voices.get(role),
                     "line_idx": i, "suffix": suffix,
                 })
+            events.segment(len(files), total)
 
             time.sleep(0.5)
 
@@ -437,6 +510,7 @@ def _patch_failed_segments(
     retries: int = 2,
 ) -> list[Path | None]:
     """Retry failed segments with a compatible backend (same voice family)."""
+    engines = _load_segment_engines(failed[0]["seg_path"].parent) if failed else {}
     for item in failed:
         logger.info("TTS [%s] patching seg %d%s: %s...",
                      backend, item["line_idx"], item["suffix"], item["chunk"][:30])
@@ -444,10 +518,13 @@ def _patch_failed_segments(
             audio_bytes = _synthesize_one(
                 item["chunk"], item["preset"]["voice"], item["preset"]["instructions"],
                 role=item["role"], backend=backend, retries=retries,
+                speaker_voice=item["speaker_voice"],
             )
             with open(item["seg_path"], "wb") as fp:
                 fp.write(audio_bytes)
             files[item["idx"]] = item["seg_path"]
+            engines[item["seg_path"].name] = backend
+            _save_segment_engines(item["seg_path"].parent, engines)
             logger.info("[TTS] Patched seg %d%s via %s",
                          item["line_idx"], item["suffix"], backend)
         except TTSError as exc:
@@ -457,12 +534,30 @@ def _patch_failed_segments(
     return files
 
 
+def discard_segments(output_dir: Path, lines: list[int]) -> int:
+    """Delete the segment files of the given dialogue lines so they are synthesized again.
+
+    Returns the number of files removed; lines without segments are skipped.
+    """
+    engines = _load_segment_engines(output_dir)
+    removed = 0
+    for i in lines:
+        for f in [*output_dir.glob(f"seg_{i:03d}.mp3"), *output_dir.glob(f"seg_{i:03d}_*.mp3")]:
+            f.unlink()
+            engines.pop(f.name, None)
+            removed += 1
+    if removed:
+        _save_segment_engines(output_dir, engines)
+    return removed
+
+
 def _clean_segments(output_dir: Path) -> int:
     """Delete all segment mp3 files in preparation for a different voice."""
     cleaned = 0
     for f in output_dir.glob("seg_*.mp3"):
         f.unlink()
         cleaned += 1
+    (output_dir / SEGMENT_ENGINES_FILE).unlink(missing_ok=True)
     if cleaned:
         logger.info("[TTS] Cleaned %d partial segments", cleaned)
     return cleaned
@@ -471,6 +566,7 @@ def _clean_segments(output_dir: Path) -> int:
 def synthesize_dialogue(
     dialogue: list[dict[str, str]],
     output_dir: Path,
+    voices: dict[str, str] | None = None,
 ) -> list[Path]:
     """
     Synthesize dialogue with smart voice-consistent fallback.
@@ -487,6 +583,8 @@ def synthesize_dialogue(
       1. Qwen TTS all → if partial fail and DashScope enabled → patches gaps
       2. Edge all  (if enabled, clean slate, different voice)
       3. DashScope all (if enabled, clean slate, paid)
+
+    ``voices`` maps speakers to voices (see ``load_voice_mapping``).
     """
     output_dir.mkdir(parents=True, exist_ok=True)
 
@@ -497,14 +595,14 @@ def synthesize_dialogue(
     if force:
         forced = _synthesize_dialogue_forced(
             dialogue, output_dir, force,
-            use_dashscope=use_dashscope, use_edge=use_edge,
+            use_dashscope=use_dashscope, use_edge=use_edge, voices=voices,
         )
         if forced is not None:
             return forced
 
     # ── Phase 1: Qwen TTS (HK local primary, US tts2api fallback) ──
     logger.info("[TTS] Phase 1: Qwen TTS (primary→fallback) for all %d lines", len(dialogue))
-    files, failed = _try_all_segments(dialogue, output_dir, "qwen_api")
+    files, failed = _try_all_segments(dialogue, output_dir, "qwen_api", voices=voices)
 
     if not failed:
         logger.info("[TTS] All %d segments done via Qwen TTS", len(files))
@@ -537,7 +635,7 @@ def synthesize_dialogue(
     if use_dashscope:
         _clean_segments(output_dir)
         logger.info("[TTS] Phase 3: DashScope for all %d lines (paid)", len(dialogue))
-        files, failed = _try_all_segments(dialogue, output_dir, "dashscope")
+        files, failed = _try_all_segments(dialogue, output_dir, "dashscope", voices=voices)
 
         if not failed:
             logger.info("[TTS] All %d segments done via DashScope", len(files))
@@ -553,9 +651,11 @@ def _synthesize_dialogue_forced(
     *,
     use_dashscope: bool,
     use_edge: bool,
+    voices: dict[str, str] | None = None,
 ) -> list[Path] | None:
     """Single-backend run when TTS_FORCE_BACKEND is set. Returns None if force value unknown."""
     allowed = {
+        "qwen_api": "qwen_api",
         "qwen_local": "qwen_local",
         "qwen_cloud": "qwen_cloud",
         "edge": "edge",
@@ -571,7 +671,7 @@ def _synthesize_dialogue_forced(
         raise TTSError("TTS_FORCE_BACKEND=dashscope but DashScope is not enabled/configured")
 
     logger.info("[TTS] Forced backend: %s for %d lines", backend, len(dialogue))
-    files, failed = _try_all_segments(dialogue, output_dir, backend)
+    files, failed = _try_all_segments(dialogue, output_dir, backend, voices=voices)
     if failed:
         raise TTSError(
             f"TTS_FORCE_BACKEND={force} failed on {len(failed)} segment(s)"
@@ -662,7 +762,9 @@ def concatenate_audio(
     chapters: list[dict] | None = None,
     num_lines: int = 0,
 ) -> list[dict]:
-    """Concatenate mp3 segments into a single mp3 file using ffmpeg.
+    """Concatenate mp3 segments into a single audio file using ffmpeg.
+
+    The output format (mp3/aac/ogg) follows the extension of output_path.
 
     When audio assets (intro/transition/outro) exist in assets/audio/:
     - Adds intro with fade-out before dialogue
@@ -673,7 +775,7 @@ def concatenate_audio(
 
     Args:
         segment_files: Ordered list of segment mp3 paths.
-        output_path: Final output mp3 path.
+        output_path: Final output audio path.
         chapters: Optional chapter info from normalize_dialogue().
         num_lines: Total number of dialogue lines (for segment mapping).
 
@@ -689,7 +791,12 @@ def concatenate_audio(
 
     # ── Simple mode: no assets → original behavior ──
     if not has_music or not has_chapters:
-        return _concatenate_simple(segment_files, output_path)
+        _concatenate_simple(segment_files, output_path)
+        if not has_chapters:
+            return []
+        # Segments are joined back to back, so chapter times are plain sums
+        line_map = _build_line_segment_map(segment_files, num_lines)
+        return _calculate_chapter_timestamps({}, chapters, line_map, line_gap=0.0)
 
     # ── Enhanced mode: music + chapter transitions ──
     return _concatenate_with_music(segment_files, output_path, assets,
@@ -879,7 +986,7 @@ def _concatenate_with_music(
             safe_path = str(p).replace("\\", "/").replace("'", "'\\''")
             f.write(f"file '{safe_path}'\n")
 
-    raw_mp3 = tmp_dir / "raw.mp3"
+    raw_mp3 = tmp_dir / f"raw{output_path.suffix}"
     cmd_concat = [
         "ffmpeg", "-y",
         "-f", "concat", "-safe", "0",
@@ -913,8 +1020,13 @@ def _calculate_chapter_timestamps(
     assets: dict[str, Path],
     chapters: list[dict],
     line_map: list[list[Path]],
+    *,
+    line_gap: float = 0.1,
 ) -> list[dict]:
-    """Calculate real start/end times for each chapter by measuring segment durations."""
+    """Calculate real start/end times for each chapter by measuring segment durations.
+
+    line_gap is the silence inserted between lines (none in simple mode).
+    """
     pos = 0.0  # current position in seconds
 
     # Intro
@@ -940,9 +1052,9 @@ def _calculate_chapter_timestamps(
             segs = line_map[line_idx] if line_idx < len(line_map) else []
             for seg_file in segs:
                 pos += _get_duration(seg_file)
-            # 0.1s gap between lines
+            # Gap between lines
             if line_idx < end_line - 1:
-                pos += 0.1
+                pos += line_gap
 
         timestamps.append({
             "title": chapter.get("title", f"Chapter {ch_idx + 1}"),
@@ -10,15 +10,18 @@ from typing import Any
 
 import pdfplumber
 
+from flying_podcast.core import events
 from flying_podcast.core.config import settings
 from flying_podcast.core.io_utils import dump_json
 from html import escape
 from flying_podcast.core.llm_client import OpenAICompatibleClient
 from flying_podcast.core.logging_utils import get_logger
 from flying_podcast.core.static_publish import public_url_for_key, publish_file as static_publish_file
-from flying_podcast.core.time_utils import beijing_today_str
+from flying_podcast.core.time_utils import beijing_now, beijing_today_str
 from flying_podcast.core.tts_client import (
     concatenate_audio,
+    discard_segments,
+    load_voice_mapping,
     synthesize_dialogue,
 )
 
@@ -167,23 +170,36 @@ def _condense_chunk(client: OpenAICompatibleClient, chunk: str, idx: int) -> str
     return chunk[:5000]
 
 
-def condense_long_text(pdf_text: str, max_chars: int = MAX_PDF_CHARS) -> str:
+def _llm_client(model: str | None = None, base_url: str | None = None) -> OpenAICompatibleClient | None:
+    """LLM client from settings, with ``model``/``base_url`` overriding them for one run.
+
+    Returns None when the key, base URL or model is still missing.
+    """
+    model = model or settings.llm_model
+    base_url = base_url or settings.llm_base_url
+    if not (settings.llm_api_key and base_url and model):
+        return None
+    return OpenAICompatibleClient(settings.llm_api_key, base_url, model)
+
+
+def condense_long_text(
+    pdf_text: str,
+    max_chars: int = MAX_PDF_CHARS,
+    *,
+    model: str | None = None,
+    base_url: str | None = None,
+) -> str:
     """Condense long text via chunked LLM extraction. Short text passes through unchanged."""
     if len(pdf_text) <= max_chars:
         return pdf_text
 
     logger.info("Text exceeds %d chars (%d chars), starting condensation...", max_chars, len(pdf_text))
 
-    if not OpenAICompatibleClient.is_configured():
+    client = _llm_client(model, base_url)
+    if client is None:
         logger.warning("LLM not configured, falling back to hard truncation")
         return pdf_text[:max_chars]
 
-    client = OpenAICompatibleClient(
-        settings.llm_api_key,
-        settings.llm_base_url,
-        settings.llm_model,
-    )
-
     chunks = _split_into_chunks(pdf_text, max_chars)
     logger.info("Split into %d chunks for condensation", len(chunks))
 
@@ -334,16 +350,16 @@ def generate_dialogue(
     *,
     llm_briefing: str = "",
     briefing_file: str | Path | None = None,
+    model: str | None = None,
+    base_url: str | None = None,
 ) -> dict[str, Any]:
-    """Use LLM to generate podcast dialogue from PDF text."""
-    if not OpenAICompatibleClient.is_configured():
-        raise RuntimeError("LLM is not configured (check LLM_API_KEY, LLM_BASE_URL, LLM_MODEL)")
+    """Use LLM to generate podcast dialogue from PDF text.
 
-    client = OpenAICompatibleClient(
-        settings.llm_api_key,
-        settings.llm_base_url,
-        settings.llm_model,
-    )
+    ``model`` and ``base_url`` override LLM_MODEL / LLM_BASE_URL for this call.
+    """
+    client = _llm_client(model, base_url)
+    if client is None:
+        raise RuntimeError("LLM is not configured (check LLM_API_KEY, LLM_BASE_URL, LLM_MODEL)")
 
     user_prompt = USER_PROMPT_TEMPLATE.format(pdf_text=pdf_text)
     briefing = _resolve_llm_briefing(briefing=llm_briefing, briefing_file=briefing_file)
@@ -354,7 +370,7 @@ def generate_dialogue(
         user_prompt += GREETING_ADDENDUM.format(greeting=settings.podcast_greeting)
         logger.info("Added greeting: %s", settings.podcast_greeting[:50])
 
-    logger.info("Generating dialogue via LLM (%s)...", settings.llm_model)
+    logger.info("Generating dialogue via LLM (%s)...", model or settings.llm_model)
     logger.info("  Prompt length: system=%d chars, user=%d chars",
                 len(SYSTEM_PROMPT), len(user_prompt))
 
@@ -670,6 +686,32 @@ def _resolve_pdf(pdf_path: str | None) -> Path:
     return pdf_file
 
 
+DEFAULT_WORKDIR_TEMPLATE = "{date}_{stem}"
+
+
+def _work_dir_name(template: str, day: str, stem: str) -> str:
+    """Render the work dir name from ``template``, falling back to the default."""
+    fields = {"date": day, "stem": stem, "time": beijing_now().strftime("%H%M%S")}
+    try:
+        name = template.format(**fields)
+    except (KeyError, IndexError, ValueError) as exc:
+        logger.warning("Invalid work dir template %r (%s), using %s", template, exc, DEFAULT_WORKDIR_TEMPLATE)
+        name = DEFAULT_WORKDIR_TEMPLATE.format(**fields)
+    # A template must not escape the output dir
+    name = re.sub(r'[\\/:*?"<>|]', "_", name).strip(" .")
+    return name or DEFAULT_WORKDIR_TEMPLATE.format(**fields)
+
+
+def _unused_work_dir(base_dir: Path, name: str) -> Path:
+    """``base_dir / name``, with ``_2``, ``_3``... appended if that already holds a script."""
+    work_dir = base_dir / name
+    n = 2
+    while (work_dir / "script.json").exists():
+        work_dir = base_dir / f"{name}_{n}"
+        n += 1
+    return work_dir
+
+
 def run_script(
     target_date: str | None = None,
     *,
@@ -678,6 +720,9 @@ def run_script(
     output_dir: str | None = None,
     llm_briefing: str = "",
     briefing_file: str | Path | None = None,
+    name_template: str | None = None,
+    model: str | None = None,
+    base_url: str | None = None,
 ) -> Path:
     """Generate podcast script from PDF (steps 1-3).
 
@@ -690,6 +735,11 @@ def run_script(
         output_dir: Custom output base directory. Defaults to settings.output_dir.
         llm_briefing: Producer notes for the LLM (emphasis, angles, must-cover points).
         briefing_file: Path to a text file with the same content (overrides llm_briefing).
+        name_template: Work dir name template with {date}, {stem} and {time}.
+            Defaults to settings.workdir_template. An existing episode is never
+            overwritten; a numeric suffix is appended instead.
+        model: LLM model for this run. Defaults to settings.llm_model.
+        base_url: LLM API URL for this run. Defaults to settings.llm_base_url.
 
     Returns:
         Path to the work directory containing script.json, dialogue.html, cover.jpg.
@@ -699,30 +749,35 @@ def run_script(
 
     pdf_name = pdf_file.stem
     base_dir = Path(output_dir) if output_dir else settings.output_dir / "podcast"
-    work_dir = base_dir / f"{day}_{pdf_name}"
+    work_dir = _unused_work_dir(base_dir, _work_dir_name(name_template or settings.workdir_template, day, pdf_name))
     work_dir.mkdir(parents=True, exist_ok=True)
 
     logger.info("=" * 60)
     logger.info("Podcast script: %s", pdf_file.name)
     logger.info("Output dir: %s", work_dir)
     logger.info("=" * 60)
+    events.work_dir(work_dir)
 
     # Step 1: Extract PDF text
     logger.info("Step 1/3: Extracting PDF text...")
+    events.progress(0.0)
     pdf_text = extract_pdf_text(pdf_file, work_dir=work_dir)
     if not pdf_text.strip():
         raise RuntimeError(f"No text extracted from PDF: {pdf_file}")
     logger.info("PDF text ready: %d chars", len(pdf_text))
 
     # Step 1.5: Condense if text exceeds limit
-    pdf_text = condense_long_text(pdf_text)
+    pdf_text = condense_long_text(pdf_text, model=model, base_url=base_url)
 
     # Step 2: Generate dialogue via LLM
     logger.info("Step 2/3: Generating dialogue script...")
+    events.progress(0.1)
     dialogue_data = generate_dialogue(
         pdf_text,
         llm_briefing=llm_briefing,
         briefing_file=briefing_file,
+        model=model,
+        base_url=base_url,
     )
 
     # Save dialogue script for reference
@@ -745,6 +800,7 @@ def run_script(
 
     # Step 3: Generate cover image
     logger.info("Step 3/3: Generating cover image...")
+    events.progress(0.8)
     cover_path = work_dir / "cover.jpg"
     generate_cover_image(pdf_file, title, cover_path)
 
@@ -767,19 +823,29 @@ def run_script(
     dump_json(work_dir / "metadata.json", meta)
 
     logger.info("Script generation complete: %s", work_dir)
+    events.progress(1.0)
     return work_dir
 
 
-def run_audio(*, work_dir: str | Path) -> Path:
+def run_audio(
+    *,
+    work_dir: str | Path,
+    audio_format: str = "mp3",
+    segments: list[int] | None = None,
+) -> Path:
     """Generate podcast audio from an existing script (steps 4-5).
 
-    Reads script.json from work_dir → TTS synthesis → MP3 concatenation.
+    Reads script.json from work_dir → TTS synthesis → audio concatenation.
 
     Args:
         work_dir: Path to the podcast work directory containing script.json.
+        audio_format: Output container/extension: "mp3", "aac" or "ogg".
+        segments: Dialogue line indices (0-based, in playback order) to
+            synthesize again. Other lines reuse their existing segment files,
+            and the whole episode is stitched together again.
 
     Returns:
-        Path to the generated MP3 file.
+        Path to the generated audio file.
     """
     work_dir = Path(work_dir)
     if not work_dir.exists():
@@ -805,11 +871,21 @@ def run_audio(*, work_dir: str | Path) -> Path:
     # Step 1: TTS synthesis
     logger.info("Step 1/2: Synthesizing %d dialogue segments...", len(flat_lines))
     segments_dir = work_dir / "segments"
-    segment_files = synthesize_dialogue(flat_lines, segments_dir)
+    if segments:
+        out_of_range = [i for i in segments if not 0 <= i < len(flat_lines)]
+        if out_of_range:
+            logger.warning("Ignoring segments beyond the script's %d lines: %s", len(flat_lines), out_of_range)
+        selected = sorted({i for i in segments if 0 <= i < len(flat_lines)})
+        removed = discard_segments(segments_dir, selected)
+        logger.info("Re-synthesizing %d selected lines (%d old segment files removed)", len(selected), removed)
+    voices = load_voice_mapping(work_dir)
+    if voices:
+        logger.info("Speaker voices from %s: %s", work_dir / "voices.json", voices)
+    segment_files = synthesize_dialogue(flat_lines, segments_dir, voices=voices)
 
     # Step 2: Concatenate (with music + chapters if assets available)
     logger.info("Step 2/2: Concatenating audio...")
-    mp3_path = work_dir / f"{title}.mp3"
+    mp3_path = work_dir / f"{title}.{audio_format}"
     chapter_timestamps = concatenate_audio(
         segment_files, mp3_path,
         chapters=chapters_info,
@@ -826,12 +902,14 @@ def run_audio(*, work_dir: str | Path) -> Path:
     mp3_filename = mp3_path.name
     static_key = f"podcast/{dir_name}/{mp3_filename}"
 
-    # Publish MP3 to the self-hosted static site
+    # Publish the audio to the self-hosted static site
+    audio_uploaded = True
     try:
         mp3_cdn_url = static_publish_file(mp3_path, static_key)
     except Exception as e:
         logger.error("Static publish failed, using constructed URL: %s", e)
         mp3_cdn_url = public_url_for_key(static_key)
+        audio_uploaded = False
 
     # Upload narration MP3 if exists
     narration_mp3_files = list(work_dir.glob("*_narration.mp3"))
@@ -849,6 +927,12 @@ def run_audio(*, work_dir: str | Path) -> Path:
 
     meta.update({
         "title": title,
+        "audio_path": str(mp3_path),
+        "audio_cdn_url": mp3_cdn_url,
+        "audio_format": audio_format,
+        # False when the URL was only constructed; publish-podcast --upload-audio retries
+        "audio_uploaded": audio_uploaded,
+        # Legacy keys, kept for readers that predate audio_format
         "mp3_path": str(mp3_path),
         "mp3_cdn_url": mp3_cdn_url,
         "narration_mp3_cdn_url": narration_mp3_cdn_url,
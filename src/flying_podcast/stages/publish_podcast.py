@@ -8,26 +8,63 @@ from __future__ import annotations
 import re
 from pathlib import Path
 
-from flying_podcast.core.config import settings
+from flying_podcast.core import events
+from flying_podcast.core.config import ROOT_DIR, settings
 from flying_podcast.core.io_utils import dump_json, load_json
 from flying_podcast.core.logging_utils import get_logger
-from flying_podcast.core.static_publish import public_url_for_key
+from flying_podcast.core.static_publish import public_url_for_key, publish_file as static_publish_file
 from flying_podcast.core.time_utils import beijing_today_str
 from flying_podcast.core.wechat import WeChatClient
 
 logger = get_logger("publish_podcast")
 
+# Draft author when metadata.json doesn't set one
+DEFAULT_AUTHOR = "飞行播客"
+
+# Series templates, maintained by the podcast-studio app
+SERIES_FILE = ROOT_DIR / "series.json"
+
+
+class PublishError(RuntimeError):
+    """Some episodes were tried but did not become drafts."""
+
 # CAAC document prefixes — PDFs with these prefixes get "阅读原文" link
 _CAAC_PREFIXES = ("AC-", "IB-", "CCAR-", "AP-", "MD-", "MH-")
 
 
+def _format_timestamp(seconds: float) -> str:
+    total = int(round(seconds))
+    hours, rest = divmod(total, 3600)
+    minutes, secs = divmod(rest, 60)
+    return f"{hours}:{minutes:02d}:{secs:02d}" if hours else f"{minutes}:{secs:02d}"
+
+
+def _build_chapters_html(chapters: list[dict]) -> str:
+    """Chapter list with start times, so listeners can seek in the audio."""
+    items = "".join(
+        '<p style="margin:0 0 4px;font-size:13px;color:#555;">'
+        f'<span style="color:#999;margin-right:8px;">{_format_timestamp(ch.get("start", 0))}</span>'
+        f'{ch.get("title", "")}'
+        '</p>'
+        for ch in chapters
+    )
+    return (
+        '<section style="margin:0 auto 20px;max-width:420px;padding:12px 16px;'
+        'background:#f8f9fa;border-radius:8px;">'
+        '<p style="font-size:14px;font-weight:600;color:#333;margin:0 0 8px;">📑 章节</p>'
+        f'{items}'
+        '</section>'
+    )
+
+
 def _build_article_html(title: str, dialogue_html: str,
                         mp3_url: str = "", narration_mp3_url: str = "",
-                        pdf_url: str = "") -> str:
-    """Build complete article HTML for WeChat — MP3 URL + dialogue card + narration + downloads.
+                        pdf_url: str = "", chapters: list[dict] | None = None) -> str:
+    """Build complete article HTML for WeChat — MP3 URL + chapters + dialogue card + narration + downloads.
 
     Audio is added manually in the WeChat editor.
     MP3 URL shown as plain text at top for easy copy-paste.
+    Chapters (from metadata.json timestamps) are listed only when there are several.
     """
     parts: list[str] = []
 
@@ -40,6 +77,9 @@ def _build_article_html(title: str, dialogue_html: str,
             '</section>'
         )
 
+    if chapters and len(chapters) > 1:
+        parts.append(_build_chapters_html(chapters))
+
     parts.append(dialogue_html)
 
     # Add PDF full-text narration section at the end
@@ -114,19 +154,96 @@ def _resolve_source_url(meta: dict) -> str:
     return static_url
 
 
+def _write_preview(ep_dir: Path, *, title: str, author: str, digest: str, source_url: str,
+                   cover_path: Path, mp3_url: str, article_html: str) -> None:
+    """Write what would be published to publish_preview.json (+ .html) for review."""
+    html_path = ep_dir / "publish_preview.html"
+    html_path.write_text(article_html, "utf-8")
+    preview = {
+        "title": title,
+        "author": author,
+        "digest": digest,
+        "source_url": source_url,
+        "cover_path": str(cover_path) if cover_path.exists() else "",
+        "mp3_url": mp3_url,
+        "html_path": str(html_path),
+        "html_chars": len(article_html),
+    }
+    dump_json(ep_dir / "publish_preview.json", preview)
+    logger.info("Dry run: preview written to %s", ep_dir / "publish_preview.json")
+
+
+def _load_series(series_id: str) -> dict:
+    """Look up a series in SERIES_FILE; raises ValueError if it isn't there."""
+    data = load_json(SERIES_FILE) if SERIES_FILE.exists() else {}
+    for series in data.get("series", []):
+        if series.get("id") == series_id:
+            return series
+    raise ValueError(f"Unknown series {series_id!r} (not in {SERIES_FILE})")
+
+
+def _series_title(series: dict, episode: int, title: str) -> str:
+    """Fill the series title template; a broken template keeps the episode title."""
+    template = series.get("title_template") or "{title}"
+    try:
+        return template.format(series=series.get("name", ""), episode=episode, title=title)
+    except (KeyError, IndexError, ValueError) as e:
+        logger.warning("Bad series title template %r (%s), using the episode title", template, e)
+        return title
+
+
+def _phase(name: str) -> None:
+    """Mark a phase; the studio shows the current phase in its log header."""
+    events.phase(name)
+
+
+def _upload_audio(ep_dir: Path, meta_path: Path, meta: dict) -> None:
+    """Publish the episode audio to the static site again, recording progress in metadata.json.
+
+    ``audio_uploaded`` is cleared first, so a run cancelled mid-upload is not
+    mistaken for a finished one.
+    """
+    audio_path = Path(meta.get("audio_path") or meta.get("mp3_path") or "")
+    if not audio_path.is_file():
+        raise FileNotFoundError(f"audio file not found: {audio_path}")
+    meta["audio_uploaded"] = False
+    dump_json(meta_path, meta)
+    url = static_publish_file(audio_path, f"podcast/{ep_dir.name}/{audio_path.name}")
+    meta.update({"audio_cdn_url": url, "mp3_cdn_url": url, "audio_uploaded": True})
+    dump_json(meta_path, meta)
+    logger.info("Audio published: %s", url)
+
+
 def run(target_date: str | None = None, *,
-        podcast_dir: str | None = None) -> list[str]:
+        podcast_dir: str | None = None,
+        dry_run: bool = False,
+        upload_audio: bool = False,
+        series: str | None = None,
+        episode: int | None = None) -> list[str]:
     """Publish podcast episodes as WeChat drafts.
 
     Args:
         target_date: Date prefix to match podcast directories (YYYY-MM-DD)
         podcast_dir: Specific podcast output directory to publish (optional)
+        dry_run: Write publish_preview.json instead of uploading anything
+        upload_audio: Upload the audio and cover again; otherwise the audio URL
+            and cover media_id saved by earlier runs are reused
+        series: Series id in series.json whose title template, author and
+            cover apply to the draft
+        episode: Episode number within ``series``
 
     Returns:
-        List of created draft media_ids
+        List of created draft media_ids (empty for a dry run)
+
+    Raises:
+        PublishError: A real run created fewer drafts than the episodes it
+            tried, after publishing the ones it could
     """
     day = target_date or beijing_today_str()
     output_base = settings.output_dir / "podcast"
+    series_info = _load_series(series) if series else None
+    if series_info is not None and episode is None:
+        raise ValueError("An episode number is required with a series")
 
     # Find podcast directories to publish
     if podcast_dir:
@@ -144,7 +261,7 @@ def run(target_date: str | None = None, *,
 
     logger.info("Found %d podcast episode(s) to publish", len(dirs_to_publish))
 
-    client = WeChatClient()
+    client = None if dry_run else WeChatClient()
     draft_ids: list[str] = []
 
     for ep_dir in dirs_to_publish:
@@ -161,12 +278,28 @@ def run(target_date: str | None = None, *,
             continue
 
         script = load_json(script_path)
-        title = script.get("title", ep_dir.name)
 
-        # Load metadata for MP3 CDN URL and source document link
+        # Load metadata for MP3 CDN URL, source document link and edited draft fields
         meta = load_json(meta_path) if meta_path.exists() else {}
-        mp3_url = meta.get("mp3_cdn_url", "")
+        if upload_audio and not dry_run:
+            _phase("上传音频")
+            try:
+                _upload_audio(ep_dir, meta_path, meta)
+            except Exception as e:
+                logger.error("Skip %s: audio upload failed: %s", ep_dir.name, e)
+                continue
+        title = meta.get("title") or script.get("title", ep_dir.name)
+        author = meta.get("author") or DEFAULT_AUTHOR
+        if series_info is not None:
+            title = _series_title(series_info, episode, title)
+            author = meta.get("author") or series_info.get("author") or DEFAULT_AUTHOR
+            series_cover = series_info.get("cover")
+            if not cover_path.exists() and series_cover:
+                cover_path = Path(series_cover)
+            logger.info("Series %s, episode %d: %s", series_info.get("name", series), episode, title)
+        mp3_url = meta.get("audio_cdn_url") or meta.get("mp3_cdn_url", "")
         narration_mp3_url = meta.get("narration_mp3_cdn_url", "")
+        chapters = meta.get("chapters") or []
         source_url = _resolve_source_url(meta)
 
         # Read dialogue HTML
@@ -176,34 +309,48 @@ def run(target_date: str | None = None, *,
             logger.warning("Skip %s: no dialogue.html", ep_dir.name)
             continue
 
-        # Upload cover image as thumb material
-        thumb_media_id = ""
-        if cover_path.exists():
+        # Digest: edited in the studio, else just the title
+        lines = script.get("dialogue", [])
+        total_chars = sum(len(l.get("text", "")) for l in lines)
+        digest = meta.get("digest") or title
+        if len(digest) > 120:
+            digest = digest[:117] + "..."
+
+        if client is None:
+            article_html = _build_article_html(title, dialogue_html, mp3_url=mp3_url,
+                                               narration_mp3_url=narration_mp3_url,
+                                               pdf_url=source_url, chapters=chapters)
+            _write_preview(ep_dir, title=title, author=author, digest=digest, source_url=source_url,
+                           cover_path=cover_path, mp3_url=mp3_url, article_html=article_html)
+            continue
+
+        # Upload cover image as thumb material, unless an earlier run already did
+        thumb_media_id = "" if upload_audio else meta.get("thumb_media_id", "")
+        if thumb_media_id:
+            logger.info("Reusing uploaded cover: %s", thumb_media_id[:30])
+        elif cover_path.exists():
+            _phase("上传封面")
             cover_bytes = cover_path.read_bytes()
             cover_name = f"{title}.jpg"
             thumb_media_id = client.upload_thumb_image_bytes(cover_bytes, file_name=cover_name)
             if thumb_media_id:
                 logger.info("Cover uploaded: %s", thumb_media_id[:30])
+                meta["thumb_media_id"] = thumb_media_id
+                dump_json(meta_path, meta)
             else:
                 logger.warning("Cover upload failed, using default thumb")
 
         # Build article HTML
         article_html = _build_article_html(title, dialogue_html, mp3_url=mp3_url,
                                            narration_mp3_url=narration_mp3_url,
-                                           pdf_url=source_url)
-
-        # Create digest summary (just the title)
-        lines = script.get("dialogue", [])
-        total_chars = sum(len(l.get("text", "")) for l in lines)
-        digest = title
-        if len(digest) > 120:
-            digest = digest[:117] + "..."
+                                           pdf_url=source_url, chapters=chapters)
 
         # Create draft
+        _phase("创建草稿")
         try:
             media_id = client.create_draft(
                 title=title,
-                author="飞行播客",
+                author=author,
                 content_html=article_html,
                 digest=digest,
                 source_url=source_url,
@@ -222,11 +369,21 @@ def run(target_date: str | None = None, *,
                 "dialogue_lines": len(lines),
                 "total_chars": total_chars,
             }
+            if series_info is not None:
+                result.update({"series": series, "episode": episode})
             dump_json(ep_dir / "publish_result.json", result)
 
         except Exception as e:
             logger.error("Failed to create draft for '%s': %s", title, e)
             continue
 
-    logger.info("Published %d/%d podcast drafts", len(draft_ids), len(dirs_to_publish))
+    if dry_run:
+        logger.info("Dry run finished, nothing was uploaded")
+    else:
+        logger.info("Published %d/%d podcast drafts", len(draft_ids), len(dirs_to_publish))
+        if len(draft_ids) < len(dirs_to_publish):
+            raise PublishError(
+                f"{len(dirs_to_publish) - len(draft_ids)} of {len(dirs_to_publish)} episode(s) "
+                "did not become drafts, see the errors above"
+            )
     return draft_ids